@@ -0,0 +1,189 @@
+//! Discrete curvature of triangle meshes.
+
+use lina::{Vec3, cross, dot};
+
+use crate::{
+    math,
+    prelude::*,
+    map::DenseMap,
+    util::{PrimitiveFloat, Pos3Like},
+};
+use super::{measure::triangle_positions, vertex_areas};
+
+
+/// Returns the discrete Gaussian curvature of each vertex: the angle deficit
+/// (2π minus the sum of the vertex's angles in its adjacent faces) divided by
+/// the vertex area (see [`vertex_areas`]).
+///
+/// Boundary vertices, isolated vertices and vertices with an area of 0 get a
+/// curvature of 0. For a closed mesh, the sum of all angle deficits is 2π
+/// times the Euler characteristic (discrete Gauss-Bonnet theorem). The given
+/// `vertex_positions` must have a position for every vertex of a face or
+/// else this function panics.
+///
+///
+/// # References
+///
+/// Meyer, Mark, et al. "Discrete differential-geometry operators for
+/// triangulated 2-manifolds." Visualization and mathematics III. 2003.
+pub fn gaussian_curvature<MeshT, MapT, ScalarT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+) -> DenseMap<VertexHandle, ScalarT>
+where
+    MeshT: TriMesh + FullAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let two = ScalarT::one() + ScalarT::one();
+    let mut deficits = mesh.vertex_handles().map(|v| (v, two * ScalarT::PI())).collect::<DenseMap<_, _>>();
+    for f in mesh.face_handles() {
+        let vertices = mesh.vertices_around_triangle(f);
+        let p = triangle_positions(mesh, vertex_positions, f);
+        for i in 0..3 {
+            deficits[vertices[i]] -= math::angle_between(p[(i + 1) % 3] - p[i], p[(i + 2) % 3] - p[i]);
+        }
+    }
+
+    let areas = vertex_areas(mesh, vertex_positions);
+    for (v, curvature) in deficits.iter_mut() {
+        *curvature = if mesh.is_boundary_vertex(v) || areas[v] == ScalarT::zero() {
+            ScalarT::zero()
+        } else {
+            *curvature / areas[v]
+        };
+    }
+
+    deficits
+}
+
+/// Returns the discrete mean curvature of each vertex, computed from the
+/// cotangent Laplacian.
+///
+/// The magnitude is half the length of the mean curvature normal (the
+/// cotangent Laplacian of the positions, divided by the vertex area, see
+/// [`vertex_areas`]). The curvature is positive where the surface is convex
+/// with respect to its face normals (e.g. everywhere on a sphere with faces
+/// in CCW order when seen from outside) and negative where it is concave.
+///
+/// Boundary vertices, isolated vertices and vertices with an area of 0 get a
+/// curvature of 0. The given `vertex_positions` must have a position for
+/// every vertex of a face or else this function panics.
+///
+///
+/// # References
+///
+/// Meyer, Mark, et al. "Discrete differential-geometry operators for
+/// triangulated 2-manifolds." Visualization and mathematics III. 2003.
+pub fn mean_curvature<MeshT, MapT, ScalarT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+) -> DenseMap<VertexHandle, ScalarT>
+where
+    MeshT: TriMesh + FullAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let two = ScalarT::one() + ScalarT::one();
+    let zero = Vec3::new(ScalarT::zero(), ScalarT::zero(), ScalarT::zero());
+
+    // Per vertex: the sum of `(cot α + cot β) * (p_j - p_i)` over all
+    // neighbors `j` and the (unnormalized) sum of the adjacent face normals.
+    let mut sums = mesh.vertex_handles().map(|v| (v, (zero, zero))).collect::<DenseMap<_, _>>();
+    for f in mesh.face_handles() {
+        let vertices = mesh.vertices_around_triangle(f);
+        let p = triangle_positions(mesh, vertex_positions, f);
+        let normal = cross(p[1] - p[0], p[2] - p[0]);
+        let double_area = normal.length();
+        if double_area == ScalarT::zero() {
+            continue;
+        }
+
+        for k in 0..3 {
+            // The angle at `k` is opposite to the edge `ij`.
+            let (i, j) = ((k + 1) % 3, (k + 2) % 3);
+            let cot = dot(p[i] - p[k], p[j] - p[k]) / double_area;
+            sums[vertices[i]].0 += (p[j] - p[i]) * cot;
+            sums[vertices[j]].0 += (p[i] - p[j]) * cot;
+            sums[vertices[k]].1 += normal;
+        }
+    }
+
+    let areas = vertex_areas(mesh, vertex_positions);
+    sums.iter()
+        .map(|(v, &(laplacian, normal))| {
+            let curvature = if mesh.is_boundary_vertex(v) || areas[v] == ScalarT::zero() {
+                ScalarT::zero()
+            } else {
+                // The mean curvature normal is `laplacian / (2 * area)` and
+                // points to the concave side.
+                let magnitude = laplacian.length() / (two * two * areas[v]);
+                if dot(laplacian, normal) > ScalarT::zero() { -magnitude } else { magnitude }
+            };
+            (v, curvature)
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use lina::Point3;
+    use crate::core::half_edge::{HalfEdgeMesh, TriConfig};
+    use super::*;
+
+    #[test]
+    fn curvature_sphere() {
+        use crate::shape::{Shape, Sphere};
+
+        let radius = 2.0;
+        let (m, positions) = Sphere { radius, ..Sphere::default() }
+            .build::<HalfEdgeMesh<TriConfig>>();
+        let areas = vertex_areas(&m, &positions);
+
+        // Discrete Gauss-Bonnet: the total curvature of a sphere is 4π.
+        let gaussian = gaussian_curvature(&m, &positions);
+        let total = m.vertex_handles().map(|v| gaussian[v] * areas[v]).sum::<f64>();
+        assert!((total - 4.0 * std::f64::consts::PI).abs() < 1e-9);
+
+        let mean = mean_curvature(&m, &positions);
+        let equator = m.vertex_handles().filter(|&v| positions[v].z.abs() < 0.5).collect::<Vec<_>>();
+        assert!(!equator.is_empty());
+        for v in equator {
+            assert!((gaussian[v] - 1.0 / (radius * radius)).abs() < 0.05, "{}", gaussian[v]);
+            assert!((mean[v] - 1.0 / radius).abs() < 0.05, "{}", mean[v]);
+        }
+    }
+
+    #[test]
+    fn curvature_flat_and_boundary() {
+        //  a - b - c
+        //  | / | / |
+        //  d - e - f
+        //  | / | / |
+        //  g - h - i
+        let mut m = HalfEdgeMesh::<TriConfig>::empty();
+        let mut positions = DenseMap::new();
+        let v = [(); 9].map(|_| m.add_vertex());
+        for (i, &vh) in v.iter().enumerate() {
+            // A tilted plane: only the center vertex is not on the boundary.
+            let (x, y) = ((i % 3) as f64, (i / 3) as f64);
+            positions.insert(vh, Point3::new(x, -y, 0.1 * x));
+        }
+        for [a, b, c, d] in [[0, 1, 3, 4], [1, 2, 4, 5], [3, 4, 6, 7], [4, 5, 7, 8]] {
+            m.add_triangle([v[a], v[c], v[b]]);
+            m.add_triangle([v[b], v[c], v[d]]);
+        }
+
+        let gaussian = gaussian_curvature(&m, &positions);
+        let mean = mean_curvature(&m, &positions);
+        assert!(gaussian[v[4]].abs() < 1e-12);
+        assert!(mean[v[4]].abs() < 1e-12);
+        for i in [0, 1, 2, 3, 5, 6, 7, 8] {
+            assert_eq!(gaussian[v[i]], 0.0);
+            assert_eq!(mean[v[i]], 0.0);
+        }
+    }
+}
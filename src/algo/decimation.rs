@@ -0,0 +1,573 @@
+//! Algorithms for reducing the number of elements of a mesh.
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BinaryHeap},
+    ops,
+};
+
+use lina::{Point3, Vec3, cross, dot};
+
+use crate::{
+    prelude::*,
+    cast, hsize,
+    map::DenseMap,
+    util::{PrimitiveFloat, Pos3Like},
+};
+
+
+/// Simplifies the triangle mesh via edge collapses guided by the quadric error
+/// metric until it has at most `target_face_count` faces.
+///
+/// Each collapse merges the two endpoints of an edge into one vertex, removing
+/// the (up to two) faces adjacent to that edge. The collapse with the smallest
+/// error is always performed first. The new vertex is placed at the position
+/// minimizing the quadric error or, if that position cannot be determined, at
+/// the best of the two endpoints and the edge midpoint. Boundary edges are
+/// constrained with additional planes perpendicular to the adjacent face, so
+/// the boundary is preserved as well as possible.
+///
+/// Collapses that would make the mesh non-manifold or flip the orientation of
+/// a face are skipped. This means that the target face count might not be
+/// reached, in which case the simplification stops once no valid collapse is
+/// left. Since a collapse removes one or two faces, the result can also have
+/// one face less than `target_face_count`.
+///
+/// The simplified mesh is built from scratch and returned together with the
+/// new vertex positions. The vertex handles of the returned mesh are not
+/// related to the vertex handles of the input mesh. Isolated vertices are kept.
+///
+/// The given `vertex_positions` must have a position for every vertex in the
+/// mesh or else this function panics.
+///
+///
+/// # References
+///
+/// Garland, Michael, and Paul S. Heckbert. "Surface simplification using
+/// quadric error metrics." Proceedings of the 24th annual conference on
+/// Computer graphics and interactive techniques. 1997.
+pub fn decimate_qem<MeshT, MapT, ScalarT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+    target_face_count: hsize,
+) -> (MeshT, DenseMap<VertexHandle, MapT::Target>)
+where
+    MeshT: TriMesh + BasicAdj + MeshMut + Empty,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    debug_assert_eq!(
+        super::assert_complete(mesh, vertex_positions),
+        Ok(()),
+        "missing vertex positions",
+    );
+
+    // Helper macro to create literal values of type `ScalarT`
+    macro_rules! lit {
+        ($x:literal) => (cast::lossless::<f32, ScalarT>($x));
+    }
+
+    // ----- (1) Copy the mesh into a simple internal representation -----------
+    //
+    // Vertices and faces are addressed by dense indices. Dead faces are never
+    // removed from the `faces_of` lists, but filtered out lazily.
+    let mut indices = DenseMap::with_capacity(mesh.num_vertices());
+    let mut positions = Vec::with_capacity(mesh.num_vertices() as usize);
+    for vh in mesh.vertex_handles() {
+        let pos = vertex_positions.get(vh).expect("missing vertex position").to_point3();
+        indices.insert(vh, positions.len());
+        positions.push(pos);
+    }
+
+    let mut state = State {
+        alive: vec![true; positions.len()],
+        version: vec![0; positions.len()],
+        faces_of: vec![Vec::new(); positions.len()],
+        quadrics: vec![Quadric::zero(); positions.len()],
+        positions,
+        faces: Vec::with_capacity(mesh.num_faces() as usize),
+        face_alive: vec![true; mesh.num_faces() as usize],
+    };
+    for fh in mesh.face_handles() {
+        let [a, b, c] = mesh.vertices_around_triangle(fh);
+        let face = [indices[a], indices[b], indices[c]];
+        for v in face {
+            state.faces_of[v].push(state.faces.len());
+        }
+        state.faces.push(face);
+    }
+
+    // ----- (2) Calculate initial quadrics ------------------------------------
+    //
+    // Each face contributes its plane to the quadrics of its vertices. For
+    // each boundary edge, we add a plane perpendicular to the face through
+    // that edge with a large weight, to keep the boundary in place.
+    let mut edges = BTreeMap::new();
+    for (fi, &face) in state.faces.iter().enumerate() {
+        let normal = state.face_normal(face);
+        if normal == Vec3::zero() {
+            continue;
+        }
+
+        let normal = normal.normalized();
+        let q = Quadric::from_plane(normal, state.positions[face[0]]);
+        for v in face {
+            state.quadrics[v] += q;
+        }
+
+        for i in 0..3 {
+            let (a, b) = (face[i], face[(i + 1) % 3]);
+            edges.entry((a.min(b), a.max(b))).or_insert((0, fi)).0 += 1;
+        }
+    }
+
+    for (&(a, b), &(count, fi)) in &edges {
+        if count != 1 {
+            continue;
+        }
+
+        let normal = state.face_normal(state.faces[fi]);
+        if normal == Vec3::zero() {
+            continue;
+        }
+
+        let dir = state.positions[b] - state.positions[a];
+        let constraint_normal = cross(dir, normal);
+        if constraint_normal == Vec3::zero() {
+            continue;
+        }
+
+        let q = Quadric::from_plane(constraint_normal.normalized(), state.positions[a])
+            * lit!(1000.0);
+        state.quadrics[a] += q;
+        state.quadrics[b] += q;
+    }
+
+    // ----- (3) Collapse edges in order of increasing error -------------------
+    let mut queue = BinaryHeap::with_capacity(edges.len());
+    for &(a, b) in edges.keys() {
+        queue.push(state.candidate(a, b));
+    }
+
+    let mut num_faces = state.faces.len() as hsize;
+    while num_faces > target_face_count {
+        let Some(c) = queue.pop() else {
+            break;
+        };
+
+        let is_outdated = !state.alive[c.a] || !state.alive[c.b]
+            || state.version[c.a] != c.version_a || state.version[c.b] != c.version_b;
+        if is_outdated || !state.can_collapse(c.a, c.b, c.pos) {
+            continue;
+        }
+
+        num_faces -= state.collapse(c.a, c.b, c.pos);
+        for n in state.neighbors(c.a) {
+            queue.push(state.candidate(c.a, n));
+        }
+    }
+
+    // ----- (4) Build the output mesh -----------------------------------------
+    let mut out = MeshT::empty();
+    let mut out_positions = DenseMap::with_capacity(mesh.num_vertices());
+    let mut new_handles = vec![None; state.positions.len()];
+    for (i, pos) in state.positions.iter().enumerate() {
+        if state.alive[i] {
+            let vh = out.add_vertex();
+            out_positions.insert(vh, pos.convert());
+            new_handles[i] = Some(vh);
+        }
+    }
+
+    out.reserve_for_faces(num_faces);
+    for (fi, face) in state.faces.iter().enumerate() {
+        if state.face_alive[fi] {
+            out.add_triangle(face.map(|v| new_handles[v].unwrap()));
+        }
+    }
+
+    (out, out_positions)
+}
+
+/// The working state of `decimate_qem`.
+struct State<S: PrimitiveFloat> {
+    positions: Vec<Point3<S>>,
+    quadrics: Vec<Quadric<S>>,
+    alive: Vec<bool>,
+
+    /// Incremented every time a vertex changes, to detect outdated entries in
+    /// the priority queue.
+    version: Vec<u32>,
+
+    /// The faces around each vertex. Might contain dead faces.
+    faces_of: Vec<Vec<usize>>,
+
+    faces: Vec<[usize; 3]>,
+    face_alive: Vec<bool>,
+}
+
+impl<S: PrimitiveFloat> State<S> {
+    /// Returns the (not normalized) normal of the given face.
+    fn face_normal(&self, [a, b, c]: [usize; 3]) -> Vec3<S> {
+        normal_of([self.positions[a], self.positions[b], self.positions[c]])
+    }
+
+    fn alive_faces_of(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        self.faces_of[v].iter().copied().filter(move |&f| self.face_alive[f])
+    }
+
+    /// Returns all vertices connected to `v` via an edge, sorted.
+    fn neighbors(&self, v: usize) -> Vec<usize> {
+        let mut out = self.alive_faces_of(v)
+            .flat_map(|f| self.faces[f])
+            .filter(|&n| n != v)
+            .collect::<Vec<_>>();
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
+    fn num_faces_of_edge(&self, a: usize, b: usize) -> usize {
+        self.alive_faces_of(a).filter(|&f| self.faces[f].contains(&b)).count()
+    }
+
+    fn is_boundary_vertex(&self, v: usize) -> bool {
+        self.neighbors(v).into_iter().any(|n| self.num_faces_of_edge(v, n) == 1)
+    }
+
+    fn candidate(&self, a: usize, b: usize) -> Candidate<S> {
+        let q = self.quadrics[a] + self.quadrics[b];
+        let pa = self.positions[a];
+        let pb = self.positions[b];
+        let mid = Point3::centroid([pa, pb]).unwrap();
+
+        let pos = q.optimum().unwrap_or_else(|| {
+            [pb, mid].into_iter().fold(pa, |best, p| {
+                if q.error(p) < q.error(best) { p } else { best }
+            })
+        });
+
+        Candidate {
+            error: q.error(pos).max(S::zero()),
+            a,
+            b,
+            version_a: self.version[a],
+            version_b: self.version[b],
+            pos,
+        }
+    }
+
+    /// Checks whether collapsing the edge between `a` and `b` into a vertex at
+    /// `pos` keeps the mesh manifold and does not flip any face.
+    fn can_collapse(&self, a: usize, b: usize, pos: Point3<S>) -> bool {
+        let num_shared = self.num_faces_of_edge(a, b);
+        if num_shared == 0 {
+            return false;
+        }
+
+        // Link condition: the only vertices adjacent to both `a` and `b` are
+        // the ones opposite to the edge in the shared faces.
+        let na = self.neighbors(a);
+        let num_common = self.neighbors(b).into_iter()
+            .filter(|n| na.binary_search(n).is_ok())
+            .count();
+        if num_common != num_shared {
+            return false;
+        }
+
+        // Collapsing an interior edge between two boundary vertices would
+        // pinch the mesh.
+        if num_shared == 2 && self.is_boundary_vertex(a) && self.is_boundary_vertex(b) {
+            return false;
+        }
+
+        for (from, other) in [(a, b), (b, a)] {
+            for f in self.alive_faces_of(from) {
+                let face = self.faces[f];
+                if face.contains(&other) {
+                    continue;
+                }
+
+                // The face must not end up as a duplicate of a face around
+                // `other`.
+                let is_duplicate = self.alive_faces_of(other).any(|g| {
+                    face.iter().all(|&v| v == from || self.faces[g].contains(&v))
+                });
+                if is_duplicate {
+                    return false;
+                }
+
+                // The face must not flip.
+                let old = self.face_normal(face);
+                let new = normal_of(face.map(|v| {
+                    if v == from { pos } else { self.positions[v] }
+                }));
+                if dot(old, new) <= S::zero() {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Collapses `b` into `a` and moves `a` to `pos`. Returns the number of
+    /// removed faces.
+    fn collapse(&mut self, a: usize, b: usize, pos: Point3<S>) -> hsize {
+        let mut removed = 0;
+        for f in std::mem::take(&mut self.faces_of[b]) {
+            if !self.face_alive[f] {
+                continue;
+            }
+
+            if self.faces[f].contains(&a) {
+                self.face_alive[f] = false;
+                removed += 1;
+            } else {
+                for v in &mut self.faces[f] {
+                    if *v == b {
+                        *v = a;
+                    }
+                }
+                self.faces_of[a].push(f);
+            }
+        }
+
+        let face_alive = &self.face_alive;
+        self.faces_of[a].retain(|&f| face_alive[f]);
+        self.alive[b] = false;
+        self.positions[a] = pos;
+        self.quadrics[a] = self.quadrics[a] + self.quadrics[b];
+        self.version[a] += 1;
+
+        removed
+    }
+}
+
+/// Returns the (not normalized) normal of the triangle with the given corners.
+fn normal_of<S: PrimitiveFloat>([a, b, c]: [Point3<S>; 3]) -> Vec3<S> {
+    cross(b - a, c - a)
+}
+
+/// A possible edge collapse in the priority queue.
+struct Candidate<S: PrimitiveFloat> {
+    error: S,
+    a: usize,
+    b: usize,
+    version_a: u32,
+    version_b: u32,
+    pos: Point3<S>,
+}
+
+// `BinaryHeap` is a max-heap, so these impls are reversed to pop the candidate
+// with the smallest error first.
+impl<S: PrimitiveFloat> Ord for Candidate<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.error.partial_cmp(&self.error).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl<S: PrimitiveFloat> PartialOrd for Candidate<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: PrimitiveFloat> PartialEq for Candidate<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S: PrimitiveFloat> Eq for Candidate<S> {}
+
+/// A symmetric 4x4 matrix representing the sum of squared distances to a set
+/// of planes. Only the upper triangle is stored, row by row.
+#[derive(Clone, Copy)]
+struct Quadric<S>([S; 10]);
+
+impl<S: PrimitiveFloat> Quadric<S> {
+    fn zero() -> Self {
+        Self([S::zero(); 10])
+    }
+
+    /// The quadric for the plane with the given unit normal through `p`.
+    fn from_plane(n: Vec3<S>, p: Point3<S>) -> Self {
+        let d = -dot(n, p.to_vec());
+        let [a, b, c] = [n.x, n.y, n.z];
+        Self([
+            a * a, a * b, a * c, a * d,
+                   b * b, b * c, b * d,
+                          c * c, c * d,
+                                 d * d,
+        ])
+    }
+
+    /// Returns the error (sum of squared distances) of the point `p`.
+    fn error(&self, p: Point3<S>) -> S {
+        let [q00, q01, q02, q03, q11, q12, q13, q22, q23, q33] = self.0;
+        let [x, y, z] = [p.x, p.y, p.z];
+        let two = S::one() + S::one();
+
+        q00 * x * x + q11 * y * y + q22 * z * z
+            + two * (q01 * x * y + q02 * x * z + q12 * y * z)
+            + two * (q03 * x + q13 * y + q23 * z)
+            + q33
+    }
+
+    /// Returns the point with minimal error or `None` if the system is
+    /// (almost) singular.
+    fn optimum(&self) -> Option<Point3<S>> {
+        let [q00, q01, q02, q03, q11, q12, q13, q22, q23, _] = self.0;
+
+        // Solve the 3x3 system via Cramer's rule.
+        let det3 = |c0: [S; 3], c1: [S; 3], c2: [S; 3]| {
+            c0[0] * (c1[1] * c2[2] - c2[1] * c1[2])
+                - c1[0] * (c0[1] * c2[2] - c2[1] * c0[2])
+                + c2[0] * (c0[1] * c1[2] - c1[1] * c0[2])
+        };
+        let c0 = [q00, q01, q02];
+        let c1 = [q01, q11, q12];
+        let c2 = [q02, q12, q22];
+        let rhs = [-q03, -q13, -q23];
+
+        let det = det3(c0, c1, c2);
+        let scale = q00.abs() + q11.abs() + q22.abs();
+        if det.abs() <= S::epsilon() * scale * scale * scale {
+            return None;
+        }
+
+        Some(Point3::new(
+            det3(rhs, c1, c2) / det,
+            det3(c0, rhs, c2) / det,
+            det3(c0, c1, rhs) / det,
+        ))
+    }
+}
+
+impl<S: PrimitiveFloat> ops::Add for Quadric<S> {
+    type Output = Self;
+    fn add(mut self, rhs: Self) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl<S: PrimitiveFloat> ops::AddAssign for Quadric<S> {
+    fn add_assign(&mut self, rhs: Self) {
+        for (a, b) in self.0.iter_mut().zip(rhs.0) {
+            *a += b;
+        }
+    }
+}
+
+impl<S: PrimitiveFloat> ops::Mul<S> for Quadric<S> {
+    type Output = Self;
+    fn mul(self, rhs: S) -> Self {
+        Self(self.0.map(|x| x * rhs))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        algo::is_closed,
+        core::half_edge::{HalfEdgeMesh, TriConfig},
+    };
+    use super::*;
+
+    type TestMesh = HalfEdgeMesh<TriConfig>;
+
+    /// A flat `n` x `n` grid of quads, each split into two triangles.
+    fn grid(n: usize) -> (TestMesh, DenseMap<VertexHandle, Point3<f64>>) {
+        let mut mesh = TestMesh::empty();
+        let mut positions = DenseMap::new();
+        let mut vertices = Vec::new();
+        for y in 0..=n {
+            for x in 0..=n {
+                let vh = mesh.add_vertex();
+                positions.insert(vh, Point3::new(x as f64, y as f64, 0.0));
+                vertices.push(vh);
+            }
+        }
+
+        let v = |x: usize, y: usize| vertices[y * (n + 1) + x];
+        for y in 0..n {
+            for x in 0..n {
+                mesh.add_triangle([v(x, y), v(x + 1, y), v(x + 1, y + 1)]);
+                mesh.add_triangle([v(x, y), v(x + 1, y + 1), v(x, y + 1)]);
+            }
+        }
+
+        (mesh, positions)
+    }
+
+    #[test]
+    fn flat_grid() {
+        let (mesh, positions) = grid(4);
+        assert_eq!(mesh.num_faces(), 32);
+
+        let (out, out_positions) = decimate_qem(&mesh, &positions, 8);
+        assert!(out.num_faces() <= 8 && out.num_faces() >= 7);
+        assert_eq!(out.num_vertices(), out_positions.num_props());
+
+        // The mesh stays flat and the corners stay in place.
+        for p in out_positions.values() {
+            assert_eq!(p.z, 0.0);
+            assert!((0.0..=4.0).contains(&p.x) && (0.0..=4.0).contains(&p.y));
+        }
+        for corner in [[0.0, 0.0], [4.0, 0.0], [0.0, 4.0], [4.0, 4.0]] {
+            assert!(out_positions.values().any(|p| [p.x, p.y] == corner));
+        }
+    }
+
+    #[test]
+    fn target_already_reached() {
+        let (mesh, positions) = grid(2);
+        let (out, out_positions) = decimate_qem(&mesh, &positions, 8);
+        assert_eq!(out.num_faces(), 8);
+        assert_eq!(out.num_vertices(), 9);
+        assert_eq!(out_positions.num_props(), 9);
+    }
+
+    #[test]
+    fn closed_mesh_stays_closed() {
+        // An octahedron
+        let mut mesh = TestMesh::empty();
+        let mut positions = DenseMap::new();
+        let [px, nx, py, ny, pz, nz] = [
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(-1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, -1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(0.0, 0.0, -1.0f64),
+        ].map(|p| {
+            let vh = mesh.add_vertex();
+            positions.insert(vh, p);
+            vh
+        });
+        mesh.add_triangle([px, py, pz]);
+        mesh.add_triangle([py, nx, pz]);
+        mesh.add_triangle([nx, ny, pz]);
+        mesh.add_triangle([ny, px, pz]);
+        mesh.add_triangle([py, px, nz]);
+        mesh.add_triangle([nx, py, nz]);
+        mesh.add_triangle([ny, nx, nz]);
+        mesh.add_triangle([px, ny, nz]);
+
+        let (out, out_positions) = decimate_qem(&mesh, &positions, 6);
+        assert_eq!(out.num_faces(), 6);
+        assert_eq!(out.num_vertices(), 5);
+        assert!(is_closed(&out));
+
+        // Simplification stops at a tetrahedron, since any further collapse
+        // would create a degenerate mesh.
+        let (out, _) = decimate_qem(&out, &out_positions, 0);
+        assert_eq!(out.num_faces(), 4);
+        assert_eq!(out.num_vertices(), 4);
+        assert!(is_closed(&out));
+    }
+}
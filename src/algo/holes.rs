@@ -0,0 +1,481 @@
+//! Finding and closing holes in meshes.
+
+use lina::cross;
+
+use crate::{
+    cast,
+    prelude::*,
+    map::DenseBitSet,
+    util::Pos3Like,
+};
+use super::FaceProvenance;
+
+
+/// Closes all holes of the mesh by filling each boundary loop with new faces.
+/// Returns the number of closed holes.
+///
+/// Boundary loops with three vertices are closed with a single triangle. All
+/// other loops are filled with a fan of triangles around a new vertex, which
+/// is placed at the centroid of the loop's vertices. The new faces have the
+/// same orientation as the faces adjacent to the hole.
+///
+/// Note that this also closes the outer boundary of a mesh: an open disk
+/// becomes a closed surface. The given `vertex_positions` must have a
+/// position for every vertex on the boundary or else this function panics.
+///
+/// If you need to know which faces were added, use
+/// [`close_holes_with_provenance`].
+pub fn close_holes<MeshT, MapT>(mesh: &mut MeshT, vertex_positions: &mut MapT) -> usize
+where
+    MeshT: TriMesh + MeshMut + EdgeAdj,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like,
+{
+    close_holes_with_provenance(mesh, vertex_positions).0
+}
+
+/// Like [`close_holes`], but additionally returns the provenance of all added
+/// faces. As all of them are new geometry, they are all mapped to `None`.
+pub fn close_holes_with_provenance<MeshT, MapT>(
+    mesh: &mut MeshT,
+    vertex_positions: &mut MapT,
+) -> (usize, FaceProvenance)
+where
+    MeshT: TriMesh + MeshMut + EdgeAdj,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like,
+{
+    let mut provenance = FaceProvenance::new();
+    let loops = boundary_loops(mesh);
+    for boundary in &loops {
+        // Each boundary edge is oriented like in its adjacent face. The new
+        // face on the other side of the edge has to use the reverse
+        // direction.
+        if let &[a, b, c] = &boundary[..] {
+            provenance.insert(mesh.add_triangle([c, b, a]), None);
+        } else {
+            let center_pos = boundary.iter()
+                .map(|&v| vertex_positions[v])
+                .centroid()
+                .unwrap();
+            let center = mesh.add_vertex();
+            vertex_positions.insert(center, center_pos);
+
+            for (i, &v) in boundary.iter().enumerate() {
+                let next = boundary[(i + 1) % boundary.len()];
+                provenance.insert(mesh.add_triangle([next, v, center]), None);
+            }
+        }
+    }
+
+    (loops.len(), provenance)
+}
+
+/// Closes all holes whose boundary loop has at most `max_boundary_length`
+/// edges and returns the number of closed holes. Larger holes are left
+/// untouched.
+///
+/// Unlike [`close_holes`], no vertices are added: each hole is filled with
+/// the triangulation of its boundary loop that has the minimal total area.
+/// This works well for planar and mildly curved holes. For strongly
+/// non-planar loops the result is still a valid triangulation, but it tends
+/// to be a "tight" surface folding into the hole rather than a smooth
+/// continuation of the surrounding surface. Triangulations that would
+/// duplicate an existing edge are not considered, as that would make the
+/// mesh non-manifold. If there is no valid triangulation, the hole is left
+/// open.
+///
+/// Note that the outer boundary of an open mesh is a boundary loop, too, and
+/// is closed as well if it is short enough. The given `vertex_positions` must
+/// have a position for every vertex on the boundary or else this function
+/// panics.
+pub fn fill_holes<MeshT, MapT>(
+    mesh: &mut MeshT,
+    vertex_positions: &MapT,
+    max_boundary_length: usize,
+) -> usize
+where
+    MeshT: TriMesh + MeshMut + EdgeAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like,
+{
+    let loops = boundary_loops(mesh);
+    let mut num_filled = 0;
+    for boundary in loops.iter().filter(|l| l.len() >= 3 && l.len() <= max_boundary_length) {
+        if let Some(triangles) = min_area_triangulation(mesh, vertex_positions, boundary) {
+            // Each boundary edge is oriented like in its adjacent face, so
+            // the new faces have to use the reverse direction.
+            for [a, b, c] in triangles {
+                mesh.add_triangle([c, b, a]);
+            }
+            num_filled += 1;
+        }
+    }
+
+    num_filled
+}
+
+/// Returns the triangulation of the given polygon with the smallest total
+/// area, using the classic `O(n³)` dynamic programming approach. Diagonals
+/// that already exist as edges in the mesh are not used. Returns `None` if no
+/// triangulation is possible under that restriction.
+fn min_area_triangulation<MeshT, MapT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+    polygon: &[VertexHandle],
+) -> Option<Vec<[VertexHandle; 3]>>
+where
+    MeshT: EdgeAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like,
+{
+    let n = polygon.len();
+    let pos = |i: usize| {
+        vertex_positions.get(polygon[i])
+            .unwrap_or_else(|| panic!("vertex position for {:?} missing", polygon[i]))
+            .to_point3()
+            .map(cast::lossy::<_, f64>)
+    };
+    let usable = |i: usize, j: usize| {
+        j == i + 1 || (i == 0 && j == n - 1)
+            || mesh.edge_between_vertices(polygon[i], polygon[j]).is_none()
+    };
+
+    // `cost[i][j]` is the minimal area to triangulate the sub-polygon
+    // `i..=j`, `split[i][j]` the apex of the triangle on the edge `i -- j` in
+    // that triangulation.
+    let mut cost = vec![vec![0.0; n]; n];
+    let mut split = vec![vec![None; n]; n];
+    for len in 2..n {
+        for i in 0..n - len {
+            let j = i + len;
+            cost[i][j] = f64::INFINITY;
+            if !usable(i, j) {
+                continue;
+            }
+
+            for k in i + 1..j {
+                if !usable(i, k) || !usable(k, j) {
+                    continue;
+                }
+                let area = cross(pos(k) - pos(i), pos(j) - pos(i)).length() / 2.0;
+                let candidate = cost[i][k] + cost[k][j] + area;
+                if candidate < cost[i][j] {
+                    cost[i][j] = candidate;
+                    split[i][j] = Some(k);
+                }
+            }
+        }
+    }
+
+    let mut out = Vec::with_capacity(n - 2);
+    let mut stack = vec![(0, n - 1)];
+    while let Some((i, j)) = stack.pop() {
+        if j - i < 2 {
+            continue;
+        }
+        let k = split[i][j]?;
+        out.push([polygon[i], polygon[k], polygon[j]]);
+        stack.push((i, k));
+        stack.push((k, j));
+    }
+    Some(out)
+}
+
+/// Returns the number of distinct boundary loops of the mesh, i.e. the number
+/// of holes (including the outer boundary of open surfaces). Edges without
+/// any adjacent face are not part of any loop. This is exactly the number of
+/// holes [`close_holes`] would close.
+pub fn num_boundary_loops<MeshT>(mesh: &MeshT) -> usize
+where
+    MeshT: TriMesh + EdgeAdj,
+{
+    boundary_loops(mesh).len()
+}
+
+/// Returns all boundary loops of the mesh. Each loop is a list of vertices in
+/// the order of the faces adjacent to the boundary, i.e. for two consecutive
+/// vertices `a` and `b`, the adjacent face contains the edge from `a` to `b`
+/// in its CCW vertex order.
+///
+/// Edges without any adjacent face are not part of any loop.
+pub fn boundary_loops<MeshT>(mesh: &MeshT) -> Vec<Vec<VertexHandle>>
+where
+    MeshT: TriMesh + EdgeAdj,
+{
+    // Returns the boundary edge oriented as in its only adjacent face.
+    let oriented = |e: EdgeHandle| {
+        let [a, b] = mesh.endpoints_of_edge(e);
+        let face = mesh.faces_of_edge(e).into_iter().next().expect("boundary edge without face");
+        let [x, y, z] = mesh.vertices_around_triangle(face);
+        if [(x, y), (y, z), (z, x)].contains(&(a, b)) {
+            [a, b]
+        } else {
+            [b, a]
+        }
+    };
+
+    let mut visited = DenseBitSet::with_capacity(mesh.num_edges());
+    let mut loops = Vec::new();
+    for start in mesh.edge_handles() {
+        if visited.is_set(start) || !mesh.is_boundary_edge(start) {
+            continue;
+        }
+
+        // Faceless edges are boundary edges, too, but they do not belong to
+        // any hole.
+        if mesh.faces_of_edge(start).len() == 0 {
+            continue;
+        }
+
+        let mut boundary = Vec::new();
+        let mut current = start;
+        loop {
+            visited.set(current);
+            let [from, to] = oriented(current);
+            boundary.push(from);
+
+            let next = mesh.edges_around_vertex(to).find(|&e| {
+                !visited.is_set(e)
+                    && mesh.is_boundary_edge(e)
+                    && mesh.faces_of_edge(e).len() == 1
+                    && oriented(e)[0] == to
+            });
+            match next {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+
+        loops.push(boundary);
+    }
+
+    loops
+}
+
+
+#[cfg(test)]
+mod tests {
+    use lina::Point3;
+    use crate::{
+        algo::{is_closed, total_surface_area},
+        core::half_edge::{HalfEdgeMesh, TriConfig},
+        map::DenseMap,
+    };
+    use super::*;
+
+    #[test]
+    fn boundary_vertices_and_edges_2d_hole() {
+        // Same mesh as `simple_2d_hole` in the core tests: the triangle
+        // (b, d, f) in the middle is a hole.
+        let mut m = HalfEdgeMesh::<TriConfig>::empty();
+        let va = m.add_vertex();
+        let vb = m.add_vertex();
+        let vc = m.add_vertex();
+        let vd = m.add_vertex();
+        let ve = m.add_vertex();
+        let vf = m.add_vertex();
+
+        m.add_triangle([va, vc, vb]);
+        m.add_triangle([vb, vc, vd]);
+        m.add_triangle([va, vb, ve]);
+        m.add_triangle([vb, vf, ve]);
+        m.add_triangle([vc, vf, vd]);
+        m.add_triangle([vc, ve, vf]);
+
+        for v in [va, vb, vc, vd, ve, vf] {
+            assert!(m.is_boundary_vertex(v));
+        }
+
+        let edge = |a, b| m.edge_between_vertices(a, b).unwrap();
+        assert!(m.is_boundary_edge(edge(vb, vd)));
+        assert!(m.is_boundary_edge(edge(vd, vf)));
+        assert!(m.is_boundary_edge(edge(vb, vf)));
+        assert!(m.is_boundary_edge(edge(va, vc)));
+        assert!(!m.is_boundary_edge(edge(va, vb)));
+        assert!(!m.is_boundary_edge(edge(vc, vf)));
+
+        // Isolated vertices count as boundary vertices, too.
+        let vg = m.add_vertex();
+        assert!(m.is_boundary_vertex(vg));
+    }
+
+    #[test]
+    fn num_boundary_loops_2d_hole() {
+        // Same mesh as above: one inner hole plus the outer boundary.
+        let mut m = HalfEdgeMesh::<TriConfig>::empty();
+        let [va, vb, vc, vd, ve, vf] = [(); 6].map(|_| m.add_vertex());
+        m.add_triangle([va, vc, vb]);
+        m.add_triangle([vb, vc, vd]);
+        m.add_triangle([va, vb, ve]);
+        m.add_triangle([vb, vf, ve]);
+        m.add_triangle([vc, vf, vd]);
+        m.add_triangle([vc, ve, vf]);
+        assert_eq!(num_boundary_loops(&m), 2);
+
+        m.add_triangle([vb, vd, vf]);
+        assert_eq!(num_boundary_loops(&m), 1);
+    }
+
+    #[test]
+    fn fill_holes_only_small_ones() {
+        // A 3x3 grid with the center cell missing.
+        let mut m = HalfEdgeMesh::<TriConfig>::empty();
+        let mut positions = DenseMap::new();
+        let mut grid = Vec::new();
+        for y in 0..4 {
+            for x in 0..4 {
+                let v = m.add_vertex();
+                positions.insert(v, Point3::new(x as f64, y as f64, 0.0));
+                grid.push(v);
+            }
+        }
+        let v = |x: usize, y: usize| grid[y * 4 + x];
+        for y in 0..3 {
+            for x in 0..3 {
+                if (x, y) != (1, 1) {
+                    m.add_triangle([v(x, y), v(x + 1, y), v(x + 1, y + 1)]);
+                    m.add_triangle([v(x, y), v(x + 1, y + 1), v(x, y + 1)]);
+                }
+            }
+        }
+        assert_eq!(num_boundary_loops(&m), 2);
+
+        // The outer boundary has 12 edges and is left alone.
+        assert_eq!(fill_holes(&mut m, &positions, 11), 1);
+        m.check_integrity();
+        assert_eq!(m.num_faces(), 18);
+        assert_eq!(num_boundary_loops(&m), 1);
+        assert_eq!(total_surface_area(&m, &positions), 9.0);
+
+        assert_eq!(fill_holes(&mut m, &positions, 11), 0);
+    }
+
+    #[test]
+    fn fill_holes_sphere_cap() {
+        use crate::shape::{Shape, Sphere};
+
+        let sphere = Sphere { num_longitudes: 8, ..Sphere::default() };
+        let (mut m, positions) = sphere.build::<HalfEdgeMesh<TriConfig>>();
+        let north_pole = m.vertex_handles().next().unwrap();
+        let cap = m.faces_around_vertex(north_pole).collect::<Vec<_>>();
+        for f in cap {
+            m.remove_face(f);
+        }
+        m.remove_isolated_vertex(north_pole);
+        assert!(!is_closed(&m));
+
+        assert_eq!(fill_holes(&mut m, &positions, 8), 1);
+        m.check_integrity();
+        assert!(is_closed(&m));
+        assert_eq!(m.num_faces(), sphere.build::<HalfEdgeMesh<TriConfig>>().0.num_faces() - 2);
+    }
+
+    #[test]
+    fn boundary_loops_2d_hole() {
+        let mut m = HalfEdgeMesh::<TriConfig>::empty();
+        let [va, vb, vc, vd, ve, vf] = [(); 6].map(|_| m.add_vertex());
+        m.add_triangle([va, vc, vb]);
+        m.add_triangle([vb, vc, vd]);
+        m.add_triangle([va, vb, ve]);
+        m.add_triangle([vb, vf, ve]);
+        m.add_triangle([vc, vf, vd]);
+        m.add_triangle([vc, ve, vf]);
+
+        // Which vertex a loop starts with is not specified, so we rotate
+        // them to start with the smallest one.
+        let mut loops = boundary_loops(&m);
+        for l in &mut loops {
+            let min_pos = (0..l.len()).min_by_key(|&i| l[i]).unwrap();
+            l.rotate_left(min_pos);
+        }
+        loops.sort();
+        assert_eq!(loops, [vec![va, vc, ve], vec![vb, vf, vd]]);
+    }
+
+    #[test]
+    fn interior_vertex_is_not_boundary() {
+        // A fan of four triangles around `center`.
+        let mut m = HalfEdgeMesh::<TriConfig>::empty();
+        let center = m.add_vertex();
+        let outer = [m.add_vertex(), m.add_vertex(), m.add_vertex(), m.add_vertex()];
+        for i in 0..4 {
+            m.add_triangle([center, outer[i], outer[(i + 1) % 4]]);
+        }
+
+        assert!(!m.is_boundary_vertex(center));
+        for v in outer {
+            assert!(m.is_boundary_vertex(v));
+            assert!(!m.is_boundary_edge(m.edge_between_vertices(center, v).unwrap()));
+        }
+    }
+
+    #[test]
+    fn close_holes_2d_hole() {
+        // Same mesh as `simple_2d_hole` in the core tests.
+        let mut m = HalfEdgeMesh::<TriConfig>::empty();
+        let mut positions = DenseMap::new();
+        let [va, vb, vc, vd, ve, vf] = [
+            Point3::new(0.0, 4.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+            Point3::new(-3.0, -2.0, 0.0),
+            Point3::new(-1.0, -1.0, 0.0),
+            Point3::new(3.0, -2.0, 0.0),
+            Point3::new(1.0, -1.0, 0.0f32),
+        ].map(|p| {
+            let vh = m.add_vertex();
+            positions.insert(vh, p);
+            vh
+        });
+        m.add_triangle([va, vc, vb]);
+        m.add_triangle([vb, vc, vd]);
+        m.add_triangle([va, vb, ve]);
+        m.add_triangle([vb, vf, ve]);
+        m.add_triangle([vc, vf, vd]);
+        m.add_triangle([vc, ve, vf]);
+        assert!(!is_closed(&m));
+
+        // The inner hole and the outer boundary are both triangles.
+        let (num_holes, provenance) = close_holes_with_provenance(&mut m, &mut positions);
+        assert_eq!(num_holes, 2);
+        assert_eq!(provenance.num_props(), 2);
+        assert!(provenance.values().all(|source| source.is_none()));
+        assert!(is_closed(&m));
+        assert_eq!(m.num_vertices(), 6);
+        assert_eq!(m.num_faces(), 8);
+        assert!(m.edge_handles().all(|e| !m.is_boundary_edge(e)));
+    }
+
+    #[test]
+    fn close_holes_fan() {
+        // A pyramid without its quadrilateral base.
+        let mut m = HalfEdgeMesh::<TriConfig>::empty();
+        let mut positions = DenseMap::new();
+        let apex = m.add_vertex();
+        positions.insert(apex, Point3::new(0.0, 0.0, 1.0));
+        let base = [
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(-1.0, 1.0, 0.0),
+            Point3::new(-1.0, -1.0, 0.0),
+            Point3::new(1.0, -1.0, 0.0f32),
+        ].map(|p| {
+            let vh = m.add_vertex();
+            positions.insert(vh, p);
+            vh
+        });
+        for i in 0..4 {
+            m.add_triangle([apex, base[i], base[(i + 1) % 4]]);
+        }
+
+        assert_eq!(close_holes(&mut m, &mut positions), 1);
+        assert!(is_closed(&m));
+        assert_eq!(m.num_vertices(), 6);
+        assert_eq!(m.num_faces(), 8);
+
+        let center = m.vertex_handles().find(|&v| v != apex && !base.contains(&v)).unwrap();
+        assert_eq!(positions[center], Point3::new(0.0, 0.0, 0.0));
+
+        // Nothing left to close.
+        assert_eq!(close_holes(&mut m, &mut positions), 0);
+    }
+}
@@ -0,0 +1,321 @@
+//! Measuring areas, centroids and edge lengths of meshes.
+
+use lina::{Point3, cross, dot};
+use smallvec::SmallVec;
+
+use crate::{
+    cast,
+    prelude::*,
+    map::DenseMap,
+    util::{PrimitiveFloat, Pos3Like},
+};
+
+
+/// Returns the area of the given triangle.
+///
+/// This is half the length of the cross product of two of the triangle's
+/// edges. The given `vertex_positions` must have a position for all three
+/// vertices of the face or else this function panics.
+pub fn face_area<MeshT, MapT, ScalarT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+    face: FaceHandle,
+) -> ScalarT
+where
+    MeshT: TriMesh + BasicAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let [a, b, c] = triangle_positions(mesh, vertex_positions, face);
+    cross(b - a, c - a).length() / (ScalarT::one() + ScalarT::one())
+}
+
+/// Returns the centroid of the given triangle, i.e. the average of its three
+/// vertex positions.
+///
+/// The given `vertex_positions` must have a position for all three vertices
+/// of the face or else this function panics.
+pub fn face_centroid<MeshT, MapT, ScalarT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+    face: FaceHandle,
+) -> Point3<ScalarT>
+where
+    MeshT: TriMesh + BasicAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    Point3::centroid(triangle_positions(mesh, vertex_positions, face)).unwrap()
+}
+
+/// Returns the sum of the areas (see [`face_area`]) of all faces.
+pub fn total_surface_area<MeshT, MapT, ScalarT>(mesh: &MeshT, vertex_positions: &MapT) -> ScalarT
+where
+    MeshT: TriMesh + BasicAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    mesh.face_handles()
+        .map(|f| face_area(mesh, vertex_positions, f))
+        .fold(ScalarT::zero(), |acc, area| acc + area)
+}
+
+/// Returns the mixed Voronoi area of each vertex, as described in "Discrete
+/// Differential-Geometry Operators for Triangulated 2-Manifolds" by Meyer et
+/// al.
+///
+/// Each triangle distributes its area among its three vertices: for
+/// non-obtuse triangles, each vertex gets the part of the triangle that is
+/// closer to it than to the other two vertices (its Voronoi region). As that
+/// region extends beyond obtuse triangles, those instead give half their area
+/// to the vertex at the obtuse angle and a quarter to each of the others. In
+/// both cases, the areas of a triangle's vertices sum to the triangle's area,
+/// so all vertex areas sum to [`total_surface_area`].
+///
+/// Isolated vertices get an area of 0. The given `vertex_positions` must have
+/// a position for every vertex of a face or else this function panics.
+pub fn vertex_areas<MeshT, MapT, ScalarT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+) -> DenseMap<VertexHandle, ScalarT>
+where
+    MeshT: TriMesh + BasicAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let two = ScalarT::one() + ScalarT::one();
+    let mut areas = mesh.vertex_handles().map(|v| (v, ScalarT::zero())).collect::<DenseMap<_, _>>();
+    for f in mesh.face_handles() {
+        let vertices = mesh.vertices_around_triangle(f);
+        let p = triangle_positions(mesh, vertex_positions, f);
+        let double_area = cross(p[1] - p[0], p[2] - p[0]).length();
+        if double_area == ScalarT::zero() {
+            continue;
+        }
+
+        let obtuse_at = (0..3).find(|&i| dot(p[(i + 1) % 3] - p[i], p[(i + 2) % 3] - p[i]) < ScalarT::zero());
+        for i in 0..3 {
+            let [a, b, c] = [p[i], p[(i + 1) % 3], p[(i + 2) % 3]];
+            let area = match obtuse_at {
+                None => {
+                    // 1/8 * (|ac|² cot(b) + |ab|² cot(c)), where the cotangent
+                    // of an angle is the dot product of the two edges divided
+                    // by the length of their cross product.
+                    let cot_b = dot(a - b, c - b) / double_area;
+                    let cot_c = dot(a - c, b - c) / double_area;
+                    let ac = (c - a).length();
+                    let ab = (b - a).length();
+                    (ac * ac * cot_b + ab * ab * cot_c) / (two * two * two)
+                }
+                Some(obtuse) if obtuse == i => double_area / (two * two),
+                Some(_) => double_area / (two * two * two),
+            };
+            areas[vertices[i]] += area;
+        }
+    }
+
+    areas
+}
+
+/// The minimum, maximum and average length of the edges of a mesh, see
+/// [`edge_length_stats`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeLengthStats<ScalarT> {
+    /// Length of the shortest edge.
+    pub min: ScalarT,
+    /// Length of the longest edge.
+    pub max: ScalarT,
+    /// Mean length of all edges.
+    pub average: ScalarT,
+}
+
+/// Returns the minimum, maximum and average length over all edges of the
+/// mesh, or `None` if the mesh has no faces.
+///
+/// Edges are derived from the faces, so this works with all meshes, even
+/// ones without `EdgeMesh`. Each edge is counted once, regardless of how many
+/// faces it is part of. The given `vertex_positions` must have a position
+/// for every vertex of a face or else this function panics.
+pub fn edge_length_stats<MeshT, MapT, ScalarT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+) -> Option<EdgeLengthStats<ScalarT>>
+where
+    MeshT: BasicAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let mut edges = std::collections::HashSet::<_, ahash::RandomState>::default();
+    for f in mesh.face_handles() {
+        let vertices = mesh.vertices_around_face(f).collect::<SmallVec<[_; 4]>>();
+        for (i, &a) in vertices.iter().enumerate() {
+            let b = vertices[(i + 1) % vertices.len()];
+            edges.insert(if a < b { [a, b] } else { [b, a] });
+        }
+    }
+
+    let pos_of = |vh| {
+        vertex_positions.get(vh)
+            .unwrap_or_else(|| panic!("vertex position for {:?} missing", vh))
+            .to_point3()
+    };
+    let mut lengths = edges.iter().map(|&[a, b]| pos_of(a).distance_from(pos_of(b)));
+    let first = lengths.next()?;
+    let mut stats = EdgeLengthStats { min: first, max: first, average: first };
+    for len in lengths {
+        stats.min = stats.min.min(len);
+        stats.max = stats.max.max(len);
+        stats.average += len;
+    }
+    stats.average /= cast::lossy::<u64, ScalarT>(edges.len() as u64);
+
+    Some(stats)
+}
+
+/// Returns the average length of all edges of the mesh, or `None` if the
+/// mesh has no faces. See [`edge_length_stats`] for more information.
+pub fn average_edge_length<MeshT, MapT, ScalarT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+) -> Option<ScalarT>
+where
+    MeshT: BasicAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    edge_length_stats(mesh, vertex_positions).map(|stats| stats.average)
+}
+
+pub(super) fn triangle_positions<MeshT, MapT, ScalarT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+    face: FaceHandle,
+) -> [Point3<ScalarT>; 3]
+where
+    MeshT: TriMesh + BasicAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    mesh.vertices_around_triangle(face).map(|vh| {
+        vertex_positions.get(vh)
+            .unwrap_or_else(|| panic!("vertex position for {:?} missing", vh))
+            .to_point3()
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{
+        SharedVertexMesh,
+        half_edge::{HalfEdgeMesh, TriConfig},
+    };
+    use super::*;
+
+    #[test]
+    fn area_and_centroid() {
+        //  (d)
+        //   | \
+        //   |  \
+        //  (a)--(c)
+        //   |  /
+        //   | /
+        //  (b)
+        let mut m = SharedVertexMesh::empty();
+        let mut positions = DenseMap::new();
+        let [va, vb, vc, vd] = [
+            Point3::new(0.0f64, 0.0, 1.0),
+            Point3::new(0.0, -3.0, 1.0),
+            Point3::new(3.0, 0.0, 1.0),
+            Point3::new(0.0, 2.0, 1.0),
+        ].map(|p| {
+            let vh = m.add_vertex();
+            positions.insert(vh, p);
+            vh
+        });
+        let fx = m.add_triangle([va, vb, vc]);
+        let fy = m.add_triangle([va, vc, vd]);
+
+        assert_eq!(face_area(&m, &positions, fx), 4.5);
+        assert_eq!(face_area(&m, &positions, fy), 3.0);
+        assert_eq!(total_surface_area(&m, &positions), 7.5);
+        assert_eq!(face_centroid(&m, &positions, fx), Point3::new(1.0, -1.0, 1.0));
+    }
+
+    #[test]
+    fn vertex_areas_sum_to_surface_area() {
+        use crate::shape::{Shape, Sphere};
+
+        let (m, positions) = Sphere::default().build::<HalfEdgeMesh<TriConfig>>();
+        let areas = vertex_areas(&m, &positions);
+        let sum = areas.values().sum::<f64>();
+        assert!((sum - total_surface_area(&m, &positions)).abs() < 1e-9);
+
+        // All vertices on the same latitude get the same area.
+        let equator = m.vertex_handles()
+            .filter(|&v| positions[v].z.abs() < 0.2)
+            .map(|v| areas[v])
+            .collect::<Vec<_>>();
+        assert!(equator.len() > 1);
+        assert!(equator.iter().all(|a| (a - equator[0]).abs() < 1e-9));
+    }
+
+    #[test]
+    fn vertex_areas_obtuse() {
+        //
+        //         (c)
+        //        /   ‾‾‾‾‾---___
+        //      (a) ------------- (b)
+        //
+        let mut m = SharedVertexMesh::empty();
+        let [va, vb, vc, vd] = [(); 4].map(|_| m.add_vertex());
+        m.add_triangle([va, vb, vc]);
+        let mut positions = DenseMap::new();
+        positions.insert(va, Point3::new(0.0, 0.0, 0.0));
+        positions.insert(vb, Point3::new(4.0, 0.0, 0.0));
+        positions.insert(vc, Point3::new(-1.0, 1.0, 0.0));
+        positions.insert(vd, Point3::new(9.0, 9.0, 9.0));
+
+        let areas = vertex_areas(&m, &positions);
+        assert_eq!(areas[va], 1.0);
+        assert_eq!(areas[vb], 0.5);
+        assert_eq!(areas[vc], 0.5);
+        assert_eq!(areas[vd], 0.0);
+    }
+
+    #[test]
+    fn edge_lengths() {
+        let mut m = SharedVertexMesh::empty();
+        let mut positions = DenseMap::new();
+        assert_eq!(average_edge_length(&m, &positions), None);
+
+        let [va, vb, vc, vd] = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.5, 0.75f64.sqrt(), 0.0),
+            Point3::new(0.5, -2.0, 0.0),
+        ].map(|p| {
+            let v = m.add_vertex();
+            positions.insert(v, p);
+            v
+        });
+        m.add_triangle([va, vb, vc]);
+        let avg = average_edge_length(&m, &positions).unwrap();
+        assert!((avg - 1.0).abs() < 1e-12);
+
+        // The shared edge `ab` is only counted once.
+        m.add_triangle([vb, va, vd]);
+        let stats = edge_length_stats(&m, &positions).unwrap();
+        let long = 4.25f64.sqrt();
+        assert!((stats.min - 1.0).abs() < 1e-12);
+        assert!((stats.max - long).abs() < 1e-12);
+        assert!((stats.average - (3.0 + 2.0 * long) / 5.0).abs() < 1e-12);
+    }
+}
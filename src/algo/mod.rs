@@ -3,15 +3,55 @@
 //! This module will grow over time.
 
 
+use lina::{Point3, Vec3, cross, dot};
+use smallvec::SmallVec;
+
 use crate::{
     VertexRef,
+    cast, math,
     prelude::*,
-    map::{DenseMap, set::DenseSet},
+    map::{DenseBitSet, DenseMap, set::DenseSet},
     util::{PrimitiveFloat, Pos3Like},
 };
 
 pub mod bounding;
+pub mod decimation;
+pub mod remeshing;
 pub mod subdivision;
+pub mod triangulation;
+
+mod curvature;
+mod holes;
+mod measure;
+mod topology;
+mod weld;
+
+pub use self::{
+    curvature::{gaussian_curvature, mean_curvature},
+    holes::{
+        boundary_loops, close_holes, close_holes_with_provenance, fill_holes,
+        num_boundary_loops,
+    },
+    measure::{
+        EdgeLengthStats, average_edge_length, edge_length_stats, face_area, face_centroid,
+        total_surface_area, vertex_areas,
+    },
+    topology::{
+        FaceAdjacency, MeshError, connected_components, non_manifold_edges,
+        non_manifold_vertices, retain_largest_component, validate,
+    },
+    weld::{weld_vertices, weld_vertices_relative},
+};
+
+
+/// Records where the faces created by an algorithm came from.
+///
+/// Algorithms that replace faces by new ones (e.g.
+/// [`triangulation::triangulate`]) map each new face to the face it replaced.
+/// Faces that are completely new geometry (e.g. created by [`close_holes`])
+/// are mapped to `None`. Faces not touched by the algorithm are not contained
+/// in the map.
+pub type FaceProvenance = DenseMap<FaceHandle, Option<FaceHandle>>;
 
 
 /// Simple smoothing algorithm: replaces each vertex position with the centroid
@@ -24,6 +64,36 @@ pub fn smooth_simple<MeshT, MapT>(
     mesh: &MeshT,
     vertex_positions: &MapT,
 ) -> DenseMap<VertexHandle, MapT::Target>
+where
+    MeshT: FullAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like,
+{
+    debug_assert_eq!(assert_complete(mesh, vertex_positions), Ok(()), "missing vertex positions");
+    mesh.vertices().map(|v| (v.handle(), smoothed_pos(v, vertex_positions))).collect()
+}
+
+/// Parallel version of [`smooth_simple`] with the exact same result. Only
+/// available with the `rayon` feature.
+#[cfg(feature = "rayon")]
+pub fn smooth_simple_par<MeshT, MapT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+) -> DenseMap<VertexHandle, MapT::Target>
+where
+    MeshT: FullAdj + Sync,
+    MapT: PropMap<VertexHandle> + Sync,
+    MapT::Target: Pos3Like + Send,
+{
+    debug_assert_eq!(assert_complete(mesh, vertex_positions), Ok(()), "missing vertex positions");
+    let len = mesh.last_vertex_handle().map_or(0, |vh| vh.to_usize() + 1);
+    DenseMap::par_from_fn(len, |vh| {
+        mesh.contains_vertex(vh).then(|| smoothed_pos(mesh.get_ref(vh), vertex_positions))
+    })
+}
+
+/// Returns the new position of `v` for [`smooth_simple`].
+fn smoothed_pos<MeshT, MapT>(v: VertexRef<'_, MeshT>, vertex_positions: &MapT) -> MapT::Target
 where
     MeshT: FullAdj,
     MapT: PropMap<VertexHandle>,
@@ -34,24 +104,311 @@ where
         *vertex_positions.get(v.handle()).expect("missing vertex position")
     };
 
-    mesh.vertices().map(|v| {
-        // If the vertex is a boundary vertex, its position doesn't change. If
-        // not, we use the centroid of all neighbors' position as new positon.
-        let new_pos = if v.is_boundary() {
-            pos_of(v)
-        } else {
-            v.adjacent_vertices()
-                .map(|n| pos_of(n))
-                .centroid()
-                .unwrap()  // is not boundary
-                .convert()
-        };
+    // If the vertex is a boundary vertex, its position doesn't change. If
+    // not, we use the centroid of all neighbors' position as new positon.
+    if v.is_boundary() {
+        pos_of(v)
+    } else {
+        v.adjacent_vertices()
+            .map(pos_of)
+            .centroid()
+            .unwrap()  // is not boundary
+            .convert()
+    }
+}
+
+
+/// Returns an iterator over all vertices of the mesh paired with their
+/// position. Vertices without a position in `vertex_positions` are skipped.
+pub fn vertices_with_positions<'a, MeshT, MapT>(
+    mesh: &'a MeshT,
+    vertex_positions: &'a MapT,
+) -> impl Iterator<Item = (VertexHandle, &'a MapT::Target)> + 'a
+where
+    MeshT: Mesh,
+    MapT: PropStore<VertexHandle>,
+{
+    mesh.vertex_handles().filter_map(move |vh| vertex_positions.get_ref(vh).map(|p| (vh, p)))
+}
+
+/// Checks that `map` has a value for every vertex of `mesh`. If not, the
+/// handles of all vertices without a value are returned in ascending order.
+///
+/// Many functions in this module require a complete map (usually vertex
+/// positions) and panic in the middle of their work if a value is missing.
+/// They call this function in a debug assertion, but you can call it upfront
+/// to handle incomplete maps gracefully.
+pub fn assert_complete<MeshT, MapT>(mesh: &MeshT, map: &MapT) -> Result<(), Vec<VertexHandle>>
+where
+    MeshT: Mesh,
+    MapT: PropMap<VertexHandle>,
+{
+    let missing = mesh.vertex_handles()
+        .filter(|&vh| !map.contains_handle(vh))
+        .collect::<Vec<_>>();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(missing)
+    }
+}
+
+
+/// Groups faces into planar segments and returns the segment ID of each face.
+///
+/// Starting from a seed face, a segment grows into all adjacent faces whose
+/// normal deviates at most `angle_tol_deg` degrees from the seed's normal.
+/// Comparing with the seed (and not with the neighbor) prevents segments from
+/// creeping around smoothly curved surfaces. Segment IDs are assigned
+/// consecutively starting at 0, in order of the seeds' face handles.
+///
+/// Face normals are computed via Newell's method, so this works for
+/// polygonal faces, too. Degenerate faces (with a zero normal) always form
+/// their own segment. The given `vertex_positions` must have a position for
+/// every vertex in the mesh or else this function panics.
+pub fn planar_segments<MeshT, MapT, ScalarT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+    angle_tol_deg: ScalarT,
+) -> DenseMap<FaceHandle, u32>
+where
+    MeshT: FullAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let normals = mesh.face_handles().map(|f| {
+        let corners = mesh.vertices_around_face(f)
+            .map(|vh| {
+                vertex_positions.get(vh)
+                    .unwrap_or_else(|| panic!("vertex position for {:?} missing", vh))
+                    .to_point3()
+                    .to_vec()
+            })
+            .collect::<Vec<_>>();
+        let n = corners.len();
+        let normal = (0..n).fold(Vec3::zero(), |acc, i| acc + cross(corners[i], corners[(i + 1) % n]));
+        let normal = if normal == Vec3::zero() { normal } else { normal.normalized() };
+
+        (f, normal)
+    }).collect::<DenseMap<_, _>>();
+
+    let min_cos = math::deg_to_rad(angle_tol_deg).cos();
+    let mut segments = DenseMap::with_capacity(mesh.num_faces());
+    let mut next_id = 0;
+    let mut stack = Vec::new();
+    for seed in mesh.face_handles() {
+        if segments.contains_handle(seed) {
+            continue;
+        }
+
+        let id = next_id;
+        next_id += 1;
+        segments.insert(seed, id);
+
+        let seed_normal = normals[seed];
+        if seed_normal == Vec3::zero() {
+            continue;
+        }
+
+        stack.push(seed);
+        while let Some(f) = stack.pop() {
+            for neighbor in mesh.faces_around_face(f) {
+                if !segments.contains_handle(neighbor) && dot(seed_normal, normals[neighbor]) >= min_cos {
+                    segments.insert(neighbor, id);
+                    stack.push(neighbor);
+                }
+            }
+        }
+    }
+
+    segments
+}
+
+
+/// Grows a selection of faces from `seed` across all edges for which
+/// `should_cross` returns `true`, and returns the selected faces.
+///
+/// This is a breadth-first search over faces, where two faces are connected
+/// if they share an edge that may be crossed. `should_cross` is called at
+/// most once per edge and never for boundary edges. The seed face is always
+/// part of the selection.
+pub fn flood_select<MeshT>(
+    mesh: &MeshT,
+    seed: FaceHandle,
+    mut should_cross: impl FnMut(EdgeHandle) -> bool,
+) -> DenseSet<FaceHandle>
+where
+    MeshT: EdgeAdj,
+{
+    let mut selected = DenseSet::with_capacity(mesh.num_faces());
+    let mut visited_edges = DenseBitSet::with_capacity(mesh.num_edges());
+    let mut queue = std::collections::VecDeque::new();
+
+    selected.insert(seed);
+    queue.push_back(seed);
+    while let Some(f) = queue.pop_front() {
+        for e in mesh.edges_around_face(f) {
+            if visited_edges.set(e) {
+                continue;
+            }
+
+            if mesh.is_boundary_edge(e) || !should_cross(e) {
+                continue;
+            }
+            for neighbor in mesh.faces_of_edge(e) {
+                if !selected.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+
+    selected
+}
+
 
-        (v.handle(), new_pos)
+/// Returns an un-indexed "triangle soup": the three vertex positions (in
+/// winding order) of each face, in the order of the face handles.
+///
+/// The positions are cast to `f32` (lossy, if necessary), as that is what
+/// consumers of this format usually expect. The given `vertex_positions` must
+/// have a position for every vertex of a face or else this function panics.
+pub fn to_triangle_soup<MeshT, MapT>(mesh: &MeshT, vertex_positions: &MapT) -> Vec<[[f32; 3]; 3]>
+where
+    MeshT: TriMesh + BasicAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like,
+{
+    mesh.face_handles().map(|f| {
+        mesh.vertices_around_triangle(f).map(|vh| {
+            let p = vertex_positions.get(vh)
+                .unwrap_or_else(|| panic!("vertex position for {:?} missing", vh));
+            [cast::lossy(p.x()), cast::lossy(p.y()), cast::lossy(p.z())]
+        })
     }).collect()
 }
 
 
+/// Rebuilds the connectivity of `src` in a new mesh of a different data
+/// structure and returns it together with the vertex handle remapping (from
+/// `src` to the new mesh).
+///
+/// Vertices and faces are added in ascending handle order. Use the returned
+/// map to carry vertex properties (like positions) over to the new mesh.
+/// Faces with more than three vertices are added as a triangle fan, as `Dst`
+/// might not support polygons. `Dst` must be able to represent the
+/// connectivity of `src`, e.g. converting a non-manifold `SharedVertexMesh`
+/// into a `HalfEdgeMesh` panics.
+///
+/// ```
+/// use lox::{
+///     algo::convert_mesh,
+///     core::{SharedVertexMesh, half_edge::{HalfEdgeMesh, TriConfig}},
+///     prelude::*,
+///     shape::{Shape, Sphere},
+/// };
+///
+/// let (src, positions) = Sphere::default().build::<SharedVertexMesh>();
+/// let (dst, map) = convert_mesh::<_, HalfEdgeMesh<TriConfig>>(&src);
+/// let dst_positions = positions.iter()
+///     .map(|(v, &p)| (map[v], p))
+///     .collect::<lox::map::DenseMap<_, _>>();
+///
+/// assert_eq!(dst.num_faces(), src.num_faces());
+/// assert_eq!(dst_positions.num_props(), dst.num_vertices());
+/// ```
+pub fn convert_mesh<SrcT, DstT>(src: &SrcT) -> (DstT, DenseMap<VertexHandle, VertexHandle>)
+where
+    SrcT: BasicAdj,
+    DstT: MeshMut,
+{
+    let mut dst = DstT::empty();
+    dst.reserve_for_vertices(src.num_vertices());
+    dst.reserve_for_faces(src.num_faces());
+
+    let map = src.vertex_handles()
+        .map(|v| (v, dst.add_vertex()))
+        .collect::<DenseMap<_, _>>();
+
+    for f in src.face_handles() {
+        let vertices = src.vertices_around_face(f)
+            .map(|v| map[v])
+            .collect::<SmallVec<[_; 4]>>();
+        for i in 1..vertices.len() - 1 {
+            dst.add_triangle([vertices[0], vertices[i], vertices[i + 1]]);
+        }
+    }
+
+    (dst, map)
+}
+
+
+/// The region [`normalize_positions`] fits the mesh into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizeTarget {
+    /// The axis aligned cube `[-0.5, 0.5]³`. The center of the bounding box
+    /// is moved to the origin and the longest side of the bounding box is
+    /// scaled to 1.
+    UnitCube,
+
+    /// The sphere with radius 1 around the origin. The centroid of all vertex
+    /// positions is moved to the origin and the vertex farthest away from it
+    /// ends up on the sphere.
+    UnitSphere,
+}
+
+/// Translates and uniformly scales the positions of all vertices of the mesh
+/// so that the mesh fits tightly into `target`.
+///
+/// Only positions of vertices in `mesh` are considered and changed. The given
+/// `vertex_positions` must have a position for every vertex in the mesh or
+/// else this function panics. If all vertices are at the same position, they
+/// are just moved to the origin. Does nothing for meshes without vertices.
+pub fn normalize_positions<MeshT, MapT, ScalarT>(
+    mesh: &MeshT,
+    vertex_positions: &mut MapT,
+    target: NormalizeTarget,
+)
+where
+    MeshT: Mesh,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let positions = mesh.vertex_handles()
+        .map(|v| vertex_positions[v].to_point3())
+        .collect::<Vec<_>>();
+    let (center, size) = match target {
+        NormalizeTarget::UnitCube => {
+            let bb = bounding::BoundingBox::around(positions.iter().copied());
+            if !bb.is_valid() {
+                return;
+            }
+
+            let size = [bb.x(), bb.y(), bb.z()].into_iter()
+                .map(|[lo, hi]| hi - lo)
+                .fold(ScalarT::zero(), ScalarT::max);
+            (bb.center(), size)
+        }
+        NormalizeTarget::UnitSphere => {
+            let Some(center) = Point3::centroid(positions.iter().copied()) else {
+                return;
+            };
+            let radius = positions.iter()
+                .map(|p| p.distance_from(center))
+                .fold(ScalarT::zero(), ScalarT::max);
+            (center, radius)
+        }
+    };
+
+    let scale = if size > ScalarT::zero() { ScalarT::one() / size } else { ScalarT::one() };
+    for (v, p) in mesh.vertex_handles().zip(positions) {
+        vertex_positions[v] = (Point3::origin() + (p - center) * scale).convert();
+    }
+}
+
 /// Returns `true` if the mesh is closed or `false` if it has holes.
 pub fn is_closed<MeshT>(mesh: &MeshT) -> bool
 where
@@ -242,3 +599,255 @@ where
 
     vertex_data
 }
+
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{
+        SharedVertexMesh,
+        half_edge::{HalfEdgeMesh, TriConfig},
+    };
+    use super::*;
+
+    #[test]
+    fn planar_segments_cube() {
+        let mut m = HalfEdgeMesh::<TriConfig>::empty();
+        let mut positions = DenseMap::new();
+        let v = [
+            Point3::new(0.0f64, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(0.0, 1.0, 1.0),
+        ].map(|p| {
+            let vh = m.add_vertex();
+            positions.insert(vh, p);
+            vh
+        });
+
+        // Each side as two triangles, all CCW when seen from the outside.
+        let sides = [[0, 3, 2, 1], [4, 5, 6, 7], [0, 1, 5, 4], [1, 2, 6, 5], [2, 3, 7, 6], [3, 0, 4, 7]];
+        let side_faces = sides.map(|[a, b, c, d]| {
+            [m.add_triangle([v[a], v[b], v[c]]), m.add_triangle([v[a], v[c], v[d]])]
+        });
+
+        let segments = planar_segments(&m, &positions, 1.0);
+        assert_eq!(segments.num_props(), 12);
+        for (i, [f0, f1]) in side_faces.into_iter().enumerate() {
+            assert_eq!(segments[f0], i as u32);
+            assert_eq!(segments[f1], i as u32);
+        }
+
+        // With a tolerance above 90°, all sides except the one opposite to
+        // the seed face join its segment.
+        let segments = planar_segments(&m, &positions, 91.0);
+        for (i, [f0, f1]) in side_faces.into_iter().enumerate() {
+            let expected = if i == 1 { 1 } else { 0 };
+            assert_eq!(segments[f0], expected);
+            assert_eq!(segments[f1], expected);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn smooth_simple_par_matches_serial() {
+        // A fan of four triangles around `vm`, with `vm` off-center.
+        let mut m = HalfEdgeMesh::<TriConfig>::empty();
+        let mut positions = DenseMap::new();
+        let [vm, va, vb, vc, vd] = [
+            Point3::new(0.3f32, 0.2, 0.5),
+            Point3::new(-1.0, 0.0, 0.0),
+            Point3::new(0.0, -1.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ].map(|p| {
+            let vh = m.add_vertex();
+            positions.insert(vh, p);
+            vh
+        });
+        m.add_triangle([vm, va, vb]);
+        m.add_triangle([vm, vb, vc]);
+        m.add_triangle([vm, vc, vd]);
+        m.add_triangle([vm, vd, va]);
+
+        let serial = smooth_simple(&m, &positions);
+        let parallel = smooth_simple_par(&m, &positions);
+        assert_eq!(serial.iter().collect::<Vec<_>>(), parallel.iter().collect::<Vec<_>>());
+        assert_eq!(parallel[vm], Point3::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn flood_select_stops_at_crease() {
+        //
+        //  (a) ----- (b) ----- (c)
+        //   |  W   ⟋  |  Y   ⟋  |
+        //   |   ⟋  X  |   ⟋  Z  |
+        //  (d) ----- (e) ----- (f)
+        //
+        // With a crease along `b -- e`.
+        let mut m = HalfEdgeMesh::<TriConfig>::empty();
+        let [va, vb, vc, vd, ve, vf] = [(); 6].map(|_| m.add_vertex());
+        let fw = m.add_triangle([va, vd, vb]);
+        let fx = m.add_triangle([vb, vd, ve]);
+        let fy = m.add_triangle([vb, ve, vc]);
+        let fz = m.add_triangle([vc, ve, vf]);
+        let crease = m.edge_between_vertices(vb, ve).unwrap();
+
+        let mut calls = 0;
+        let selection = flood_select(&m, fw, |e| {
+            calls += 1;
+            e != crease
+        });
+        assert_eq!(selection.handles().collect::<Vec<_>>(), [fw, fx]);
+        assert_eq!(calls, 2);
+
+        let selection = flood_select(&m, fz, |_| true);
+        assert_eq!(selection.handles().collect::<Vec<_>>(), [fw, fx, fy, fz]);
+    }
+
+    #[test]
+    fn triangle_soup_tetrahedron() {
+        let mut m = SharedVertexMesh::empty();
+        let mut positions = DenseMap::new();
+        let v = [
+            Point3::new(0.0f64, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 0.1),
+        ].map(|p| {
+            let vh = m.add_vertex();
+            positions.insert(vh, p);
+            vh
+        });
+        m.add_triangle([v[0], v[2], v[1]]);
+        m.add_triangle([v[0], v[1], v[3]]);
+        m.add_triangle([v[0], v[3], v[2]]);
+        m.add_triangle([v[1], v[2], v[3]]);
+
+        let soup = to_triangle_soup(&m, &positions);
+        assert_eq!(soup.len(), 4);
+        assert_eq!(soup[0], [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]]);
+        assert_eq!(soup[3], [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.1]]);
+    }
+
+    #[test]
+    fn normalize_box() {
+        // Only vertices matter, so the box corners are not connected by faces.
+        let mut m = SharedVertexMesh::empty();
+        let mut positions = DenseMap::<_, Point3<f64>>::new();
+        for i in 0..8 {
+            let p = Point3::new(
+                if i & 1 == 0 { 3.0 } else { 7.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { 0.0 } else { 2.0 },
+            );
+            positions.insert(m.add_vertex(), p);
+        }
+
+        let mut cube = positions.clone();
+        normalize_positions(&m, &mut cube, NormalizeTarget::UnitCube);
+        let bb = bounding::BoundingBox::around(cube.values().copied());
+        let expected = [[-0.5, 0.5], [-0.25, 0.25], [-0.25, 0.25]];
+        for (actual, expected) in [bb.x(), bb.y(), bb.z()].into_iter().zip(expected) {
+            assert!((actual[0] - expected[0]).abs() < 1e-8 && (actual[1] - expected[1]).abs() < 1e-8);
+        }
+
+        let mut sphere = positions.clone();
+        normalize_positions(&m, &mut sphere, NormalizeTarget::UnitSphere);
+        let center = Point3::centroid(sphere.values().copied()).unwrap();
+        assert!(center.to_vec().length() < 1e-12);
+        let radius = sphere.values().map(|p| p.to_vec().length()).fold(0.0, f64::max);
+        assert!((radius - 1.0).abs() < 1e-12);
+
+        // Positions are left alone for empty meshes.
+        let before = positions.values().copied().collect::<Vec<_>>();
+        normalize_positions(&SharedVertexMesh::empty(), &mut positions, NormalizeTarget::UnitCube);
+        assert_eq!(positions.values().copied().collect::<Vec<_>>(), before);
+    }
+
+    #[test]
+    fn assert_complete_partial_map() {
+        let mut m = SharedVertexMesh::empty();
+        let [va, vb, vc, vd] = [m.add_vertex(), m.add_vertex(), m.add_vertex(), m.add_vertex()];
+        m.add_triangle([va, vb, vc]);
+
+        let mut positions = DenseMap::new();
+        positions.insert(va, Point3::new(0.0, 0.0, 0.0));
+        positions.insert(vc, Point3::new(0.0, 1.0, 0.0));
+        assert_eq!(assert_complete(&m, &positions), Err(vec![vb, vd]));
+
+        positions.insert(vb, Point3::new(1.0, 0.0, 0.0));
+        positions.insert(vd, Point3::new(1.0, 1.0, 0.0));
+        assert_eq!(assert_complete(&m, &positions), Ok(()));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "missing vertex positions")]
+    fn smooth_simple_incomplete_map() {
+        let mut m = HalfEdgeMesh::<TriConfig>::empty();
+        let [va, vb, vc] = [m.add_vertex(), m.add_vertex(), m.add_vertex()];
+        m.add_triangle([va, vb, vc]);
+
+        let mut positions = DenseMap::new();
+        positions.insert(va, Point3::new(0.0, 0.0, 0.0));
+        smooth_simple(&m, &positions);
+    }
+
+    #[test]
+    fn convert_mesh_roundtrip() {
+        use crate::{core::half_edge::PolyConfig, shape::{Shape, Sphere}};
+
+        let (src, _) = Sphere::default().build::<SharedVertexMesh>();
+        let (he, to_he) = convert_mesh::<_, HalfEdgeMesh<TriConfig>>(&src);
+        he.check_integrity();
+        assert_eq!(he.num_vertices(), src.num_vertices());
+        assert_eq!(he.num_faces(), src.num_faces());
+        for (old, new) in src.face_handles().zip(he.face_handles()) {
+            let expected = src.vertices_around_triangle(old).map(|v| to_he[v]);
+            assert_rotated_eq!(he.vertices_around_triangle(new), expected);
+        }
+
+        let (back, to_back) = convert_mesh::<_, SharedVertexMesh>(&he);
+        assert_eq!(back.num_faces(), src.num_faces());
+        assert!(src.vertex_handles().all(|v| to_back[to_he[v]] == v));
+
+        // Polygons are split into triangles.
+        let mut quad = HalfEdgeMesh::<PolyConfig>::empty();
+        let v = [quad.add_vertex(), quad.add_vertex(), quad.add_vertex(), quad.add_vertex()];
+        let f = quad.add_face(&v);
+        let (tri, map) = convert_mesh::<_, SharedVertexMesh>(&quad);
+        let v = quad.vertices_around_face(f).map(|v| map[v]).collect::<Vec<_>>();
+        let faces = tri.face_handles().map(|f| tri.vertices_around_triangle(f)).collect::<Vec<_>>();
+        assert_eq!(faces, [[v[0], v[1], v[2]], [v[0], v[2], v[3]]]);
+    }
+
+    #[test]
+    fn vertices_with_positions_triangle() {
+        let mut m = SharedVertexMesh::empty();
+        let va = m.add_vertex();
+        let vb = m.add_vertex();
+        let vc = m.add_vertex();
+        let vd = m.add_vertex();
+        m.add_triangle([va, vb, vc]);
+
+        let mut positions = DenseMap::new();
+        positions.insert(va, Point3::new(0.0, 0.0, 0.0));
+        positions.insert(vb, Point3::new(1.0, 0.0, 0.0));
+        positions.insert(vc, Point3::new(0.0, 1.0, 0.0));
+
+        let mut pairs = vertices_with_positions(&m, &positions)
+            .map(|(vh, p)| (vh, *p))
+            .collect::<Vec<_>>();
+        pairs.sort_by_key(|(vh, _)| *vh);
+        assert_eq!(pairs, [
+            (va, Point3::new(0.0, 0.0, 0.0)),
+            (vb, Point3::new(1.0, 0.0, 0.0)),
+            (vc, Point3::new(0.0, 1.0, 0.0)),
+        ]);
+        assert!(!pairs.iter().any(|(vh, _)| *vh == vd));
+    }
+}
@@ -0,0 +1,434 @@
+//! Algorithms for improving the triangulation of a mesh.
+
+use lina::{Point3, Vec3, cross, dot};
+
+use crate::{
+    prelude::*,
+    cast,
+    util::{PrimitiveFloat, Pos3Like},
+};
+
+
+/// Isotropic remeshing: tries to make all edges roughly `target_edge_len`
+/// long and all triangles roughly equilateral.
+///
+/// Each of the `iterations` performs the four standard steps:
+///
+/// 1. Split all edges longer than 4/3 of the target length at their midpoint.
+/// 2. Collapse all edges shorter than 4/5 of the target length into their
+///    midpoint (or into their boundary vertex, if they have one), unless that
+///    would create edges longer than 4/3 of the target length, make the mesh
+///    non-manifold or flip a face.
+/// 3. Flip interior edges if that brings the valences of the four involved
+///    vertices closer to 6 (4 for boundary vertices).
+/// 4. Move all interior vertices towards the centroid of their neighbors,
+///    but only tangentially (in the plane orthogonal to the vertex normal).
+///
+/// The boundary of the mesh is preserved: boundary edges are only split and
+/// boundary vertices never move. Thus, the boundary can only get finer, never
+/// coarser. The result is not projected back onto the original surface, so
+/// curved surfaces will slightly shrink.
+///
+/// The given `vertex_positions` must have a position for every vertex in the
+/// mesh or else this function panics. Positions of new vertices are inserted
+/// and positions of removed vertices are removed.
+///
+///
+/// # References
+///
+/// Botsch, Mario, and Leif Kobbelt. "A remeshing approach to multiresolution
+/// modeling." Proceedings of the 2004 Eurographics/ACM SIGGRAPH symposium on
+/// Geometry processing. 2004.
+pub fn isotropic_remesh<MeshT, MapT, ScalarT>(
+    mesh: &mut MeshT,
+    vertex_positions: &mut MapT,
+    target_edge_len: ScalarT,
+    iterations: usize,
+)
+where
+    MeshT: TriMesh + EdgeMesh + MeshMut + EdgeAdj,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    debug_assert_eq!(
+        super::assert_complete(mesh, vertex_positions),
+        Ok(()),
+        "missing vertex positions",
+    );
+
+    // Helper macro to create literal values of type `ScalarT`
+    macro_rules! lit {
+        ($x:literal) => (cast::lossless::<f32, ScalarT>($x));
+    }
+
+    let high = target_edge_len * lit!(4.0) / lit!(3.0);
+    let low = target_edge_len * lit!(4.0) / lit!(5.0);
+
+    for _ in 0..iterations {
+        split_long_edges(mesh, vertex_positions, high);
+        collapse_short_edges(mesh, vertex_positions, low, high);
+        equalize_valences(mesh, vertex_positions);
+        relax_tangentially(mesh, vertex_positions);
+    }
+}
+
+fn pos_of<MapT, ScalarT>(vertex_positions: &MapT, v: VertexHandle) -> Point3<ScalarT>
+where
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    vertex_positions.get_ref(v).expect("missing vertex position").to_point3()
+}
+
+fn edge_len<MeshT, MapT, ScalarT>(mesh: &MeshT, vertex_positions: &MapT, e: EdgeHandle) -> ScalarT
+where
+    MeshT: EdgeAdj,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let [a, b] = mesh.endpoints_of_edge(e);
+    pos_of(vertex_positions, a).distance_from(pos_of(vertex_positions, b))
+}
+
+/// Returns the (not normalized) normal of the triangle with the given
+/// corners.
+fn normal_of<S: PrimitiveFloat>([a, b, c]: [Point3<S>; 3]) -> Vec3<S> {
+    cross(b - a, c - a)
+}
+
+fn split_long_edges<MeshT, MapT, ScalarT>(mesh: &mut MeshT, vertex_positions: &mut MapT, high: ScalarT)
+where
+    MeshT: TriMesh + EdgeMesh + MeshMut + EdgeAdj,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    // Splitting an edge halves its length, so we need several passes for edges
+    // much longer than `high`.
+    loop {
+        let long_edges = mesh.edge_handles()
+            .filter(|&e| edge_len(mesh, vertex_positions, e) > high)
+            .collect::<Vec<_>>();
+        if long_edges.is_empty() {
+            break;
+        }
+
+        for e in long_edges {
+            let [a, b] = mesh.endpoints_of_edge(e);
+            let mid = Point3::centroid([
+                pos_of(vertex_positions, a),
+                pos_of(vertex_positions, b),
+            ]).unwrap();
+
+            let res = mesh.split_edge_with_faces(e);
+            vertex_positions.insert(res.vertex, mid.convert());
+        }
+    }
+}
+
+fn collapse_short_edges<MeshT, MapT, ScalarT>(
+    mesh: &mut MeshT,
+    vertex_positions: &mut MapT,
+    low: ScalarT,
+    high: ScalarT,
+)
+where
+    MeshT: TriMesh + EdgeMesh + MeshMut + EdgeAdj,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let candidates = mesh.edge_handles().collect::<Vec<_>>();
+    for e in candidates {
+        // Previous collapses might have removed this edge.
+        if !mesh.contains_edge(e) || edge_len(mesh, vertex_positions, e) >= low {
+            continue;
+        }
+
+        // The boundary is kept fixed: interior vertices can be collapsed into
+        // boundary vertices, but not the other way around. Two interior
+        // vertices meet at the edge's midpoint.
+        let [a, b] = match mesh.endpoints_of_edge(e) {
+            [a, b] if mesh.is_boundary_vertex(b) => [b, a],
+            endpoints => endpoints,
+        };
+        if mesh.is_boundary_vertex(b) {
+            continue;
+        }
+        let pos = if mesh.is_boundary_vertex(a) {
+            pos_of(vertex_positions, a)
+        } else {
+            Point3::centroid([pos_of(vertex_positions, a), pos_of(vertex_positions, b)]).unwrap()
+        };
+
+        if can_collapse(mesh, vertex_positions, a, b, pos, high) {
+            collapse(mesh, a, b);
+            vertex_positions.remove(b);
+            vertex_positions.insert(a, pos.convert());
+        }
+    }
+}
+
+/// Checks if collapsing `b` into `a` (moving `a` to `pos`) is allowed.
+fn can_collapse<MeshT, MapT, ScalarT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+    a: VertexHandle,
+    b: VertexHandle,
+    pos: Point3<ScalarT>,
+    high: ScalarT,
+) -> bool
+where
+    MeshT: TriMesh + EdgeAdj,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    // Link condition: exactly the two vertices opposite to the edge are
+    // adjacent to both `a` and `b`. Otherwise, the collapse would create a
+    // non-manifold mesh.
+    let na = mesh.vertices_around_vertex(a).collect::<Vec<_>>();
+    let nb = mesh.vertices_around_vertex(b).collect::<Vec<_>>();
+    if nb.iter().filter(|n| na.contains(n)).count() != 2 || na.len() + nb.len() < 7 {
+        return false;
+    }
+
+    // The collapse must not create long edges.
+    let too_long = na.iter().chain(&nb)
+        .any(|&n| n != a && n != b && pos.distance_from(pos_of(vertex_positions, n)) > high);
+    if too_long {
+        return false;
+    }
+
+    // No face must be flipped.
+    for (from, other) in [(a, b), (b, a)] {
+        for f in mesh.faces_around_vertex(from) {
+            let vertices = mesh.vertices_around_triangle(f);
+            if vertices.contains(&other) {
+                continue;
+            }
+
+            let old = normal_of(vertices.map(|v| pos_of(vertex_positions, v)));
+            let new = normal_of(vertices.map(|v| {
+                if v == from { pos } else { pos_of(vertex_positions, v) }
+            }));
+            if dot(old, new) <= ScalarT::zero() {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// Collapses the interior edge between `a` and `b` by removing `b` and
+/// connecting all its faces to `a` instead.
+fn collapse<MeshT>(mesh: &mut MeshT, a: VertexHandle, b: VertexHandle)
+where
+    MeshT: TriMesh + MeshMut + EdgeAdj,
+{
+    // The faces around `b` in cyclic order, starting right after one of the
+    // two faces we remove. That way, re-adding the faces always extends an
+    // existing fan.
+    let mut faces = mesh.faces_around_vertex(b)
+        .map(|f| (f, mesh.vertices_around_triangle(f)))
+        .collect::<Vec<_>>();
+    let start = faces.iter().position(|(_, vs)| vs.contains(&a)).unwrap();
+    faces.rotate_left(start + 1);
+
+    for &(f, _) in &faces {
+        mesh.remove_face(f);
+    }
+    mesh.remove_isolated_vertex(b);
+
+    for (_, vertices) in faces {
+        if !vertices.contains(&a) {
+            mesh.add_triangle(vertices.map(|v| if v == b { a } else { v }));
+        }
+    }
+}
+
+fn equalize_valences<MeshT, MapT, ScalarT>(mesh: &mut MeshT, vertex_positions: &MapT)
+where
+    MeshT: TriMesh + EdgeMesh + MeshMut + EdgeAdj,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let deviation = |mesh: &MeshT, v: VertexHandle, change: isize| {
+        let target = if mesh.is_boundary_vertex(v) { 4 } else { 6 };
+        let valence = mesh.vertices_around_vertex(v).count() as isize + change;
+        (valence - target).abs()
+    };
+
+    let candidates = mesh.edge_handles().collect::<Vec<_>>();
+    for e in candidates {
+        if mesh.is_boundary_edge(e) {
+            continue;
+        }
+
+        //        c
+        //       / \
+        //      /   \
+        //     a --- b
+        //      \   /
+        //       \ /
+        //        d
+        let [a, b] = mesh.endpoints_of_edge(e);
+        let mut faces = mesh.faces_of_edge(e).into_iter();
+        let (Some(f0), Some(f1)) = (faces.next(), faces.next()) else {
+            continue;
+        };
+        let opposite = |f| {
+            mesh.vertices_around_triangle(f).into_iter().find(|&v| v != a && v != b).unwrap()
+        };
+        let c = opposite(f0);
+        let d = opposite(f1);
+
+        // The new edge must not exist already.
+        if c == d || mesh.edge_between_vertices(c, d).is_some() {
+            continue;
+        }
+
+        let before = deviation(mesh, a, 0) + deviation(mesh, b, 0)
+            + deviation(mesh, c, 0) + deviation(mesh, d, 0);
+        let after = deviation(mesh, a, -1) + deviation(mesh, b, -1)
+            + deviation(mesh, c, 1) + deviation(mesh, d, 1);
+        if after >= before {
+            continue;
+        }
+
+        // The quad `a, d, b, c` must be convex, otherwise the flipped faces
+        // would overlap.
+        let [pa, pb, pc, pd] = [a, b, c, d].map(|v| pos_of(vertex_positions, v));
+        let n0 = normal_of(mesh.vertices_around_triangle(f0).map(|v| pos_of(vertex_positions, v)));
+        let flipped_ok = dot(normal_of([pc, pa, pd]), n0) > ScalarT::zero()
+            && dot(normal_of([pd, pb, pc]), n0) > ScalarT::zero()
+            || dot(normal_of([pc, pd, pa]), n0) > ScalarT::zero()
+            && dot(normal_of([pd, pc, pb]), n0) > ScalarT::zero();
+        if !flipped_ok {
+            continue;
+        }
+
+        mesh.flip_edge(e);
+    }
+}
+
+fn relax_tangentially<MeshT, MapT, ScalarT>(mesh: &MeshT, vertex_positions: &mut MapT)
+where
+    MeshT: TriMesh + EdgeAdj,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let new_positions = mesh.vertex_handles()
+        .filter(|&v| !mesh.is_boundary_vertex(v))
+        .map(|v| {
+            let p = pos_of(vertex_positions, v);
+            let q = Point3::centroid(
+                mesh.vertices_around_vertex(v).map(|n| pos_of(vertex_positions, n))
+            ).unwrap();
+
+            // Area weighted vertex normal.
+            let normal = mesh.faces_around_vertex(v)
+                .map(|f| {
+                    normal_of(mesh.vertices_around_triangle(f).map(|v| pos_of(vertex_positions, v)))
+                })
+                .fold(Vec3::zero(), |acc, n| acc + n);
+
+            let new_pos = if normal == Vec3::zero() {
+                q
+            } else {
+                let n = normal.normalized();
+                q + n * dot(n, p - q)
+            };
+
+            (v, new_pos)
+        })
+        .collect::<Vec<_>>();
+
+    for (v, pos) in new_positions {
+        vertex_positions.insert(v, pos.convert());
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::half_edge::{HalfEdgeMesh, TriConfig},
+        map::DenseMap,
+    };
+    use super::*;
+
+    type TestMesh = HalfEdgeMesh<TriConfig>;
+
+    /// Creates a triangulated `n × n` grid of quads with side length `size`
+    /// in the plane `z = 0`.
+    fn plane(n: usize, size: f64) -> (TestMesh, DenseMap<VertexHandle, Point3<f64>>) {
+        let mut mesh = TestMesh::empty();
+        let mut positions = DenseMap::new();
+        let step = size / n as f64;
+        let grid = (0..=n).map(|y| {
+            (0..=n).map(|x| {
+                let vh = mesh.add_vertex();
+                positions.insert(vh, Point3::new(x as f64 * step, y as f64 * step, 0.0));
+                vh
+            }).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+
+        for y in 0..n {
+            for x in 0..n {
+                mesh.add_triangle([grid[y][x], grid[y][x + 1], grid[y + 1][x + 1]]);
+                mesh.add_triangle([grid[y][x], grid[y + 1][x + 1], grid[y + 1][x]]);
+            }
+        }
+
+        (mesh, positions)
+    }
+
+    fn mean_edge_len(
+        mesh: &TestMesh,
+        positions: &DenseMap<VertexHandle, Point3<f64>>,
+        filter: impl Fn(EdgeHandle) -> bool,
+    ) -> f64 {
+        let lengths = mesh.edge_handles()
+            .filter(|&e| filter(e))
+            .map(|e| edge_len(mesh, positions, e))
+            .collect::<Vec<_>>();
+        lengths.iter().sum::<f64>() / lengths.len() as f64
+    }
+
+    #[test]
+    fn refine_plane() {
+        // Edges of the grid are 1 and √2 long.
+        let (mut mesh, mut positions) = plane(8, 8.0);
+        isotropic_remesh(&mut mesh, &mut positions, 0.5, 5);
+        mesh.check_integrity();
+
+        assert!(positions.values().all(|p| p.z == 0.0));
+        assert_eq!(positions.num_props(), mesh.num_vertices());
+        let mean = mean_edge_len(&mesh, &positions, |_| true);
+        assert!((mean - 0.5).abs() < 0.1, "mean edge length {} too far from 0.5", mean);
+    }
+
+    #[test]
+    fn coarsen_plane() {
+        // Edges of the grid are 0.25 and √2/4 long. As the boundary cannot be
+        // coarsened, we only look at the interior.
+        let (mut mesh, mut positions) = plane(32, 8.0);
+        isotropic_remesh(&mut mesh, &mut positions, 1.0, 5);
+        mesh.check_integrity();
+
+        assert!(positions.values().all(|p| p.z == 0.0));
+        assert_eq!(positions.num_props(), mesh.num_vertices());
+        let mean = mean_edge_len(&mesh, &positions, |e| {
+            let [a, b] = mesh.endpoints_of_edge(e);
+            !mesh.is_boundary_vertex(a) && !mesh.is_boundary_vertex(b)
+        });
+        assert!((mean - 1.0).abs() < 0.2, "mean edge length {} too far from 1.0", mean);
+    }
+}
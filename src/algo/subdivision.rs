@@ -4,7 +4,7 @@ use std::{
     collections::HashMap,
 };
 
-use lina::Point3;
+use lina::{Point3, Vec3};
 
 use crate::{
     prelude::*,
@@ -317,3 +317,230 @@ where
         vertex_positions[vh] = new_positions[vh];
     }
 }
+
+
+/// One step of Loop subdivision for triangle meshes.
+///
+/// Each edge gets a new vertex ("odd vertex") and each triangle is replaced
+/// by four new triangles. Old vertices ("even vertices") are repositioned
+/// using the standard Loop weights. On boundaries, the usual cubic B-spline
+/// rules are used: new boundary edge vertices are placed at the edge
+/// midpoint and old boundary vertices only take their two boundary neighbors
+/// into account. Isolated vertices and boundary vertices with more than two
+/// boundary neighbors (multi-blade vertices) keep their position.
+///
+/// The subdivided mesh is built from scratch and returned together with the
+/// new vertex positions. The vertex handles of the returned mesh are not
+/// related to the vertex handles of the input mesh.
+///
+/// The given `vertex_positions` must have a position for every vertex in the
+/// mesh or else this function panics.
+///
+///
+/// # References
+///
+/// Loop, Charles. "Smooth subdivision surfaces based on triangles." Master's
+/// thesis, University of Utah, 1987.
+pub fn loop_subdivide<MeshT, MapT, ScalarT>(
+    mesh: &MeshT,
+    vertex_positions: &MapT,
+) -> (MeshT, DenseMap<VertexHandle, MapT::Target>)
+where
+    MeshT: TriMesh + EdgeMesh + MeshMut + EdgeAdj + Empty,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    debug_assert_eq!(
+        super::assert_complete(mesh, vertex_positions),
+        Ok(()),
+        "missing vertex positions",
+    );
+
+    // Helper macro to create literal values of type `ScalarT`
+    macro_rules! lit {
+        ($x:literal) => (cast::lossless::<f32, ScalarT>($x));
+    }
+
+    let pos_of = |vh: VertexHandle| {
+        vertex_positions.get(vh).expect("missing vertex position").to_point3().to_vec()
+    };
+
+    let mut out = MeshT::empty();
+    let mut out_positions = DenseMap::with_capacity(mesh.num_vertices() + mesh.num_edges());
+    out.reserve_for_vertices(mesh.num_vertices() + mesh.num_edges());
+    out.reserve_for_faces(mesh.num_faces() * 4);
+
+    // ----- (1) Even vertices: reposition all old vertices -------------------
+    let mut even_vertices = DenseMap::with_capacity(mesh.num_vertices());
+    for v in mesh.vertices() {
+        let vh = v.handle();
+        let old_pos = pos_of(vh);
+
+        let new_pos = if v.is_isolated() {
+            old_pos
+        } else if v.is_boundary() {
+            let mut neighbors = v.adjacent_edges()
+                .filter(|e| e.is_boundary())
+                .map(|e| e.opposite_endpoint_of(vh).handle());
+
+            match (neighbors.next(), neighbors.next(), neighbors.next()) {
+                (Some(a), Some(b), None) => {
+                    old_pos * lit!(0.75) + (pos_of(a) + pos_of(b)) * lit!(0.125)
+                }
+                _ => old_pos,
+            }
+        } else {
+            let mut valence = 0;
+            let mut sum = Vec3::zero();
+            for n in v.adjacent_vertices() {
+                valence += 1;
+                sum += pos_of(n.handle());
+            }
+
+            let n = cast::lossy::<hsize, ScalarT>(valence);
+            let t = lit!(0.375) + lit!(0.25) * (lit!(2.0) * ScalarT::PI() / n).cos();
+            let beta = (lit!(0.625) - t * t) / n;
+            old_pos * (lit!(1.0) - n * beta) + sum * beta
+        };
+
+        let new_vh = out.add_vertex();
+        out_positions.insert(new_vh, (Point3::origin() + new_pos).convert());
+        even_vertices.insert(vh, new_vh);
+    }
+
+    // ----- (2) Odd vertices: one new vertex per edge ------------------------
+    let mut odd_vertices = DenseMap::with_capacity(mesh.num_edges());
+    for e in mesh.edges() {
+        let [a, b] = mesh.endpoints_of_edge(e.handle());
+        let mut faces = mesh.faces_of_edge(e.handle()).into_iter();
+
+        let pos = if let (Some(fa), Some(fb)) = (faces.next(), faces.next()) {
+            // The two vertices opposite to the edge in the adjacent faces.
+            let opposite = |fh| {
+                mesh.vertices_around_triangle(fh)
+                    .into_iter()
+                    .find(|&v| v != a && v != b)
+                    .unwrap()
+            };
+            (pos_of(a) + pos_of(b)) * lit!(0.375)
+                + (pos_of(opposite(fa)) + pos_of(opposite(fb))) * lit!(0.125)
+        } else {
+            (pos_of(a) + pos_of(b)) * lit!(0.5)
+        };
+
+        let new_vh = out.add_vertex();
+        out_positions.insert(new_vh, (Point3::origin() + pos).convert());
+        odd_vertices.insert(e.handle(), new_vh);
+    }
+
+    // ----- (3) Four new faces per old face ----------------------------------
+    //
+    //                 a
+    //                / \
+    //               /   \
+    //             ca --- ab
+    //             / \   / \
+    //            /   \ /   \
+    //           c --- bc --- b
+    //
+    for f in mesh.face_handles() {
+        let [a, b, c] = mesh.vertices_around_triangle(f);
+        let odd_between = |x, y| {
+            let e = mesh.edge_between_vertices(x, y)
+                .expect("bug: no edge between two vertices of a face");
+            odd_vertices[e]
+        };
+        let ab = odd_between(a, b);
+        let bc = odd_between(b, c);
+        let ca = odd_between(c, a);
+        let [a, b, c] = [even_vertices[a], even_vertices[b], even_vertices[c]];
+
+        out.add_triangle([a, ab, ca]);
+        out.add_triangle([ab, b, bc]);
+        out.add_triangle([ca, bc, c]);
+        out.add_triangle([ab, bc, ca]);
+    }
+
+    (out, out_positions)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        algo::is_closed,
+        core::half_edge::{HalfEdgeMesh, TriConfig},
+    };
+    use super::*;
+
+    type TestMesh = HalfEdgeMesh<TriConfig>;
+
+    #[test]
+    fn loop_single_triangle() {
+        let mut mesh = TestMesh::empty();
+        let mut positions = DenseMap::new();
+        let va = mesh.add_vertex();
+        let vb = mesh.add_vertex();
+        let vc = mesh.add_vertex();
+        positions.insert(va, Point3::new(0.0f64, 0.0, 0.0));
+        positions.insert(vb, Point3::new(8.0, 0.0, 0.0));
+        positions.insert(vc, Point3::new(0.0, 8.0, 0.0));
+        mesh.add_triangle([va, vb, vc]);
+
+        let (out, out_positions) = loop_subdivide(&mesh, &positions);
+        assert_eq!(out.num_vertices(), 6);
+        assert_eq!(out.num_faces(), 4);
+        assert_eq!(out.num_edges(), 9);
+        assert_eq!(out_positions.num_props(), 6);
+
+        // Corners use the boundary rule (3/4 own position, 1/8 for both
+        // neighbors), new vertices are edge midpoints.
+        let mut all = out_positions.values().copied().collect::<Vec<_>>();
+        all.sort_by(|a, b| (a.x, a.y).partial_cmp(&(b.x, b.y)).unwrap());
+        assert_eq!(all, [
+            Point3::new(0.0, 4.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(1.0, 6.0, 0.0),
+            Point3::new(4.0, 0.0, 0.0),
+            Point3::new(4.0, 4.0, 0.0),
+            Point3::new(6.0, 1.0, 0.0),
+        ]);
+    }
+
+    #[test]
+    fn loop_tetrahedron() {
+        let mut mesh = TestMesh::empty();
+        let mut positions = DenseMap::new();
+        let v = [
+            Point3::new(1.0f64, 1.0, 1.0),
+            Point3::new(1.0, -1.0, -1.0),
+            Point3::new(-1.0, 1.0, -1.0),
+            Point3::new(-1.0, -1.0, 1.0),
+        ].map(|p| {
+            let vh = mesh.add_vertex();
+            positions.insert(vh, p);
+            vh
+        });
+        mesh.add_triangle([v[0], v[1], v[2]]);
+        mesh.add_triangle([v[0], v[3], v[1]]);
+        mesh.add_triangle([v[0], v[2], v[3]]);
+        mesh.add_triangle([v[1], v[3], v[2]]);
+
+        let (out, out_positions) = loop_subdivide(&mesh, &positions);
+        assert_eq!(out.num_vertices(), 10);
+        assert_eq!(out.num_faces(), 16);
+        assert_eq!(out.num_edges(), 24);
+        assert!(is_closed(&out));
+
+        // With valence 3, beta is 3/16, so the old vertices end up at 1/4 of
+        // their original distance to the origin. The new edge vertices end up
+        // on the coordinate axes with distance 1/2 to the origin.
+        for p in out_positions.values() {
+            let len = (p.x * p.x + p.y * p.y + p.z * p.z).sqrt();
+            let is_even = (len - 3.0f64.sqrt() / 4.0).abs() < 1e-9;
+            let is_odd = (len - 0.5).abs() < 1e-9;
+            assert!(is_even || is_odd, "unexpected position {:?}", p);
+        }
+    }
+}
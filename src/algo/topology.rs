@@ -0,0 +1,513 @@
+//! Checking and analyzing the connectivity of meshes.
+
+use std::{collections::HashMap, fmt};
+
+use smallvec::SmallVec;
+
+use crate::{
+    prelude::*,
+    map::DenseMap,
+};
+
+
+/// A problem found by [`validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshError {
+    /// The face references a vertex that does not exist in the mesh.
+    DanglingVertex {
+        face: FaceHandle,
+        vertex: VertexHandle,
+    },
+
+    /// The face references the same vertex more than once.
+    DuplicateVertex {
+        face: FaceHandle,
+        vertex: VertexHandle,
+    },
+
+    /// The face has fewer than three vertices.
+    TooFewVertices {
+        face: FaceHandle,
+        count: usize,
+    },
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DanglingVertex { face, vertex } => {
+                write!(f, "face {:?} references non-existing vertex {:?}", face, vertex)
+            }
+            Self::DuplicateVertex { face, vertex } => {
+                write!(f, "face {:?} references vertex {:?} more than once", face, vertex)
+            }
+            Self::TooFewVertices { face, count } => {
+                write!(f, "face {:?} has only {} vertices", face, count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+/// Checks the face-to-vertex connectivity of the mesh and returns the first
+/// problem found.
+///
+/// Unlike [`Mesh::check_integrity`], which is a debugging tool that panics,
+/// this function is meant to validate meshes built from untrusted input. It
+/// checks that each face has at least three vertices, that all of them exist
+/// in the mesh and that no vertex appears twice in the same face.
+pub fn validate<MeshT>(mesh: &MeshT) -> Result<(), MeshError>
+where
+    MeshT: BasicAdj,
+{
+    for face in mesh.face_handles() {
+        let vertices = mesh.vertices_around_face(face).collect::<Vec<_>>();
+        if vertices.len() < 3 {
+            return Err(MeshError::TooFewVertices { face, count: vertices.len() });
+        }
+
+        for (i, &vertex) in vertices.iter().enumerate() {
+            if !mesh.contains_vertex(vertex) {
+                return Err(MeshError::DanglingVertex { face, vertex });
+            }
+            if vertices[..i].contains(&vertex) {
+                return Err(MeshError::DuplicateVertex { face, vertex });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Precomputed face adjacency for meshes that cannot answer face-to-face
+/// queries themselves (or only slowly), like `SharedVertexMesh`.
+///
+/// Two faces are adjacent if they share an edge, i.e. two consecutive
+/// vertices. Building this is `O(F)`, after which [`neighbors`][Self::neighbors]
+/// is cheap. The adjacency is not updated when the mesh changes.
+#[derive(Debug, Clone)]
+pub struct FaceAdjacency {
+    /// Maps each edge (as sorted vertex pair) to the faces containing it.
+    edges: HashMap<[VertexHandle; 2], SmallVec<[FaceHandle; 2]>, ahash::RandomState>,
+
+    /// The edges of each face, in the face's vertex order.
+    face_edges: DenseMap<FaceHandle, SmallVec<[[VertexHandle; 2]; 3]>>,
+}
+
+impl FaceAdjacency {
+    /// Computes the adjacency of all faces of the given mesh.
+    pub fn build<MeshT: BasicAdj>(mesh: &MeshT) -> Self {
+        let mut edges = HashMap::<_, SmallVec<_>, _>::default();
+        let mut face_edges = DenseMap::with_capacity(mesh.num_faces());
+        for f in mesh.face_handles() {
+            let vertices = mesh.vertices_around_face(f).collect::<SmallVec<[_; 3]>>();
+            let keys = (0..vertices.len())
+                .map(|i| {
+                    let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+                    if a < b { [a, b] } else { [b, a] }
+                })
+                .collect::<SmallVec<_>>();
+
+            for &key in &keys {
+                edges.entry(key).or_default().push(f);
+            }
+            face_edges.insert(f, keys);
+        }
+
+        Self { edges, face_edges }
+    }
+
+    /// Returns all faces sharing an edge with `face`, in the order of the
+    /// face's edges. Faces adjacent via a non-manifold edge are all
+    /// returned. Panics if `face` was not in the mesh this was built from.
+    pub fn neighbors(&self, face: FaceHandle) -> impl Iterator<Item = FaceHandle> + '_ {
+        self.face_edges[face].iter()
+            .flat_map(move |key| self.edges[key].iter().copied().filter(move |&f| f != face))
+    }
+
+    /// Returns the faces containing the edge between `a` and `b` (in any
+    /// direction). The slice is empty if there is no such edge.
+    pub fn faces_of_edge(&self, a: VertexHandle, b: VertexHandle) -> &[FaceHandle] {
+        let key = if a < b { [a, b] } else { [b, a] };
+        self.edges.get(&key).map(|faces| &faces[..]).unwrap_or(&[])
+    }
+}
+
+
+/// Returns all edges that are shared by more than two faces, as vertex pairs
+/// `(a, b)` with `a < b`, sorted.
+///
+/// The edges are derived from the faces' vertex lists, so this works for
+/// meshes without explicit edges like `SharedVertexMesh`, which are also the
+/// only ones able to represent such edges.
+pub fn non_manifold_edges<MeshT: BasicAdj>(mesh: &MeshT) -> Vec<(VertexHandle, VertexHandle)> {
+    let mut counts = HashMap::<_, u32, ahash::RandomState>::default();
+    for f in mesh.face_handles() {
+        let vertices = mesh.vertices_around_face(f).collect::<SmallVec<[_; 3]>>();
+        for i in 0..vertices.len() {
+            let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+            *counts.entry(if a < b { (a, b) } else { (b, a) }).or_default() += 1;
+        }
+    }
+
+    let mut out = counts.into_iter()
+        .filter(|&(_, count)| count > 2)
+        .map(|(edge, _)| edge)
+        .collect::<Vec<_>>();
+    out.sort();
+    out
+}
+
+/// Returns all vertices whose adjacent faces form more than one fan (also
+/// called "blade"), in ascending order. Two faces around a vertex belong to
+/// the same fan if they are connected via edges incident to the vertex.
+///
+/// Like [`non_manifold_edges`], this only uses the faces' vertex lists and
+/// thus works for all meshes. Isolated vertices are not reported.
+pub fn non_manifold_vertices<MeshT: BasicAdj>(mesh: &MeshT) -> Vec<VertexHandle> {
+    // For each vertex: the two neighbors in each adjacent face.
+    let mut corners = DenseMap::<_, SmallVec<[[VertexHandle; 2]; 6]>>::with_capacity(mesh.num_vertices());
+    for f in mesh.face_handles() {
+        let vertices = mesh.vertices_around_face(f).collect::<SmallVec<[_; 3]>>();
+        let len = vertices.len();
+        for i in 0..len {
+            let neighbors = [vertices[(i + len - 1) % len], vertices[(i + 1) % len]];
+            corners.get_or_insert_with(vertices[i], SmallVec::new).push(neighbors);
+        }
+    }
+
+    corners.iter()
+        .filter(|(_, corners)| {
+            // Union-find over the corners: corners sharing a neighbor vertex
+            // share an edge and thus belong to the same fan.
+            fn root(parents: &mut [usize], mut i: usize) -> usize {
+                while parents[i] != i {
+                    parents[i] = parents[parents[i]];
+                    i = parents[i];
+                }
+                i
+            }
+
+            let mut parents = (0..corners.len()).collect::<SmallVec<[_; 6]>>();
+            for i in 0..corners.len() {
+                for j in i + 1..corners.len() {
+                    if corners[i].iter().any(|v| corners[j].contains(v)) {
+                        let (ri, rj) = (root(&mut parents, i), root(&mut parents, j));
+                        parents[ri] = rj;
+                    }
+                }
+            }
+            (0..corners.len()).filter(|&i| root(&mut parents, i) == i).count() > 1
+        })
+        .map(|(v, _)| v)
+        .collect()
+}
+
+/// Returns the connected components of the mesh, each as a list of its faces
+/// in ascending handle order.
+///
+/// Two faces are in the same component if they are connected via a chain of
+/// faces where each pair of consecutive faces shares at least one vertex.
+/// Components are ordered by their smallest face handle. Isolated vertices
+/// are not part of any component.
+pub fn connected_components<MeshT: BasicAdj>(mesh: &MeshT) -> Vec<Vec<FaceHandle>> {
+    fn root(parents: &mut DenseMap<VertexHandle, VertexHandle>, mut v: VertexHandle) -> VertexHandle {
+        while parents[v] != v {
+            parents[v] = parents[parents[v]];
+            v = parents[v];
+        }
+        v
+    }
+
+    // Union-find over the vertices: all vertices of a face are merged.
+    let mut parents = mesh.vertex_handles().map(|v| (v, v)).collect::<DenseMap<_, _>>();
+    for f in mesh.face_handles() {
+        let mut vertices = mesh.vertices_around_face(f);
+        let first = vertices.next().expect("face without vertices");
+        for v in vertices {
+            let (ra, rb) = (root(&mut parents, first), root(&mut parents, v));
+            parents[rb] = ra;
+        }
+    }
+
+    let mut index_of_root = DenseMap::new();
+    let mut components = Vec::<Vec<_>>::new();
+    for f in mesh.face_handles() {
+        let first = mesh.vertices_around_face(f).next().unwrap();
+        let index = *index_of_root.get_or_insert_with(root(&mut parents, first), || {
+            components.push(Vec::new());
+            components.len() - 1
+        });
+        components[index].push(f);
+    }
+
+    components
+}
+
+/// Removes all connected components (see [`connected_components`]) except
+/// the one with the most faces. Returns the number of components found and
+/// the number of removed faces.
+///
+/// If several components have the most faces, the first one is kept. The
+/// vertices of removed components are removed as well, including their
+/// positions in `vertex_positions`. Isolated vertices are left untouched.
+pub fn retain_largest_component<MeshT, MapT>(
+    mesh: &mut MeshT,
+    vertex_positions: &mut MapT,
+) -> (usize, usize)
+where
+    MeshT: MeshMut + BasicAdj,
+    MapT: PropStoreMut<VertexHandle>,
+{
+    let components = connected_components(mesh);
+
+    // `max_by_key` returns the last maximum, so we search in reverse order.
+    let Some(largest) = (0..components.len()).rev().max_by_key(|&i| components[i].len()) else {
+        return (0, 0);
+    };
+
+    let mut removed_faces = 0;
+    for (_, faces) in components.iter().enumerate().filter(|&(i, _)| i != largest) {
+        let mut vertices = Vec::new();
+        for &f in faces {
+            vertices.extend(mesh.vertices_around_face(f));
+            mesh.remove_face(f);
+        }
+        vertices.sort();
+        vertices.dedup();
+        for v in vertices {
+            mesh.remove_isolated_vertex(v);
+            vertex_positions.remove(v);
+        }
+        removed_faces += faces.len();
+    }
+
+    (components.len(), removed_faces)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use lina::Point3;
+    use crate::core::{
+        SharedVertexMesh,
+        half_edge::{HalfEdgeMesh, TriConfig},
+    };
+    use super::*;
+
+    #[test]
+    fn validate_shared_vertex_mesh() {
+        let mut m = SharedVertexMesh::empty();
+        let va = m.add_vertex();
+        let vb = m.add_vertex();
+        let vc = m.add_vertex();
+        let vd = m.add_vertex();
+        m.add_triangle([va, vb, vc]);
+        m.add_triangle([vb, vd, vc]);
+        assert_eq!(validate(&m), Ok(()));
+    }
+
+    /// A polygon mesh that stores its faces' vertex lists without any checks,
+    /// to build meshes that `validate` has to reject. It has the vertices
+    /// `V0` to `V(num_vertices - 1)`.
+    #[derive(Debug)]
+    struct UncheckedMesh {
+        num_vertices: crate::hsize,
+        faces: Vec<Vec<VertexHandle>>,
+    }
+
+    impl Empty for UncheckedMesh {
+        fn empty() -> Self {
+            Self { num_vertices: 0, faces: Vec::new() }
+        }
+    }
+
+    impl Mesh for UncheckedMesh {
+        type FaceKind = crate::core::PolyFaces;
+        type Orientable = typebool::False;
+
+        fn num_vertices(&self) -> crate::hsize {
+            self.num_vertices
+        }
+        fn next_vertex_handle_from(&self, start: VertexHandle) -> Option<VertexHandle> {
+            Some(start).filter(|v| v.idx() < self.num_vertices)
+        }
+        fn last_vertex_handle(&self) -> Option<VertexHandle> {
+            self.num_vertices.checked_sub(1).map(VertexHandle::new)
+        }
+        fn num_faces(&self) -> crate::hsize {
+            self.faces.len() as crate::hsize
+        }
+        fn next_face_handle_from(&self, start: FaceHandle) -> Option<FaceHandle> {
+            Some(start).filter(|f| f.to_usize() < self.faces.len())
+        }
+        fn last_face_handle(&self) -> Option<FaceHandle> {
+            self.faces.len().checked_sub(1).map(FaceHandle::from_usize)
+        }
+        fn num_edges(&self) -> crate::hsize {
+            unreachable!()
+        }
+        fn next_edge_handle_from(&self, _: EdgeHandle) -> Option<EdgeHandle> {
+            unreachable!()
+        }
+        fn last_edge_handle(&self) -> Option<EdgeHandle> {
+            unreachable!()
+        }
+    }
+
+    impl BasicAdj for UncheckedMesh {
+        fn vertices_around_triangle(&self, _: FaceHandle) -> [VertexHandle; 3] {
+            unreachable!()
+        }
+
+        type VerticesAroundFaceIter<'s> = std::iter::Copied<std::slice::Iter<'s, VertexHandle>>;
+
+        fn vertices_around_face(&self, face: FaceHandle) -> Self::VerticesAroundFaceIter<'_> {
+            self.faces[face.to_usize()].iter().copied()
+        }
+    }
+
+    #[test]
+    fn validate_errors() {
+        let [va, vb, vc, vd] = [0, 1, 2, 3].map(VertexHandle::new);
+        let mesh = |faces: &[&[VertexHandle]]| UncheckedMesh {
+            num_vertices: 3,
+            faces: faces.iter().map(|f| f.to_vec()).collect(),
+        };
+        let f1 = FaceHandle::new(1);
+
+        assert_eq!(validate(&mesh(&[&[va, vb, vc]])), Ok(()));
+        assert_eq!(
+            validate(&mesh(&[&[va, vb, vc], &[vc, vb, vd]])),
+            Err(MeshError::DanglingVertex { face: f1, vertex: vd }),
+        );
+        assert_eq!(
+            validate(&mesh(&[&[va, vb, vc], &[va, vb, vc, vb]])),
+            Err(MeshError::DuplicateVertex { face: f1, vertex: vb }),
+        );
+        assert_eq!(
+            validate(&mesh(&[&[va, vb, vc], &[va, vb]])),
+            Err(MeshError::TooFewVertices { face: f1, count: 2 }),
+        );
+
+        let err = validate(&mesh(&[&[va, vd, vc]])).unwrap_err();
+        assert_eq!(err.to_string(), "face F0 references non-existing vertex V3");
+    }
+
+    #[test]
+    fn face_adjacency_tetrahedron() {
+        fn tetrahedron<MeshT: MeshMut + TriMesh>() -> (MeshT, [VertexHandle; 4]) {
+            let mut m = MeshT::empty();
+            let v = [m.add_vertex(), m.add_vertex(), m.add_vertex(), m.add_vertex()];
+            m.add_triangle([v[0], v[1], v[2]]);
+            m.add_triangle([v[0], v[3], v[1]]);
+            m.add_triangle([v[0], v[2], v[3]]);
+            m.add_triangle([v[1], v[3], v[2]]);
+            (m, v)
+        }
+
+        let (full, _) = tetrahedron::<HalfEdgeMesh<TriConfig>>();
+        let (shared, [va, vb, _, _]) = tetrahedron::<SharedVertexMesh>();
+        let adjacency = FaceAdjacency::build(&shared);
+
+        for f in full.face_handles() {
+            let mut expected = full.faces_around_face(f).collect::<Vec<_>>();
+            let mut actual = adjacency.neighbors(f).collect::<Vec<_>>();
+            expected.sort();
+            actual.sort();
+            assert_eq!(actual, expected);
+        }
+
+        let faces = adjacency.faces_of_edge(vb, va);
+        assert_eq!(faces, &[FaceHandle::from_usize(0), FaceHandle::from_usize(1)]);
+        assert!(adjacency.faces_of_edge(va, va).is_empty());
+    }
+
+    #[test]
+    fn face_adjacency_faces_of_edge_strip() {
+        //
+        //  (a) ----- (b)
+        //   |  X   ⟋  |
+        //   |   ⟋  Y  |
+        //  (c) ----- (d)
+        //
+        let mut m = SharedVertexMesh::empty();
+        let [va, vb, vc, vd] = [(); 4].map(|_| m.add_vertex());
+        let fx = m.add_triangle([va, vc, vb]);
+        let fy = m.add_triangle([vb, vc, vd]);
+        let adjacency = FaceAdjacency::build(&m);
+
+        assert_eq!(adjacency.faces_of_edge(vb, vc), &[fx, fy]);
+        assert_eq!(adjacency.faces_of_edge(vc, vb), &[fx, fy]);
+        assert_eq!(adjacency.faces_of_edge(va, vb), &[fx]);
+        assert_eq!(adjacency.faces_of_edge(vd, vc), &[fy]);
+        assert!(adjacency.faces_of_edge(va, vd).is_empty());
+    }
+
+    #[test]
+    fn non_manifold_edges_three_fins() {
+        // Three triangles sharing the edge `a -- b`, plus a fourth triangle
+        // attached regularly to one of them.
+        let mut m = SharedVertexMesh::empty();
+        let [va, vb, vc, vd, ve, vf] = [(); 6].map(|_| m.add_vertex());
+        m.add_triangle([va, vb, vc]);
+        m.add_triangle([vb, va, vd]);
+        let fin = m.add_triangle([va, vb, ve]);
+        m.add_triangle([vb, vf, vc]);
+        assert_eq!(non_manifold_edges(&m), [(va, vb)]);
+
+        m.remove_face(fin);
+        assert!(non_manifold_edges(&m).is_empty());
+    }
+
+    #[test]
+    fn non_manifold_vertices_two_blades() {
+        // Same mesh as `vertex_with_two_blades` in the core tests.
+        fn two_blades<MeshT: MeshMut + TriMesh>() -> (MeshT, VertexHandle) {
+            let mut m = MeshT::empty();
+            let [va, vb, vc, vd, ve] = [(); 5].map(|_| m.add_vertex());
+            m.add_triangle([va, vc, vb]);
+            m.add_triangle([va, vd, ve]);
+            (m, va)
+        }
+
+        let (m, va) = two_blades::<HalfEdgeMesh<TriConfig>>();
+        assert_eq!(non_manifold_vertices(&m), [va]);
+
+        let (mut m, va) = two_blades::<SharedVertexMesh>();
+        assert_eq!(non_manifold_vertices(&m), [va]);
+
+        // Connecting both blades turns them into a single fan.
+        let vs = m.vertex_handles().collect::<Vec<_>>();
+        m.add_triangle([va, vs[1], vs[3]]);
+        assert!(non_manifold_vertices(&m).is_empty());
+    }
+
+    #[test]
+    fn retain_largest_component_two_parts() {
+        //    a       e
+        //   /|\     / \
+        //  b-c-d   f---g    h (isolated)
+        let mut m = SharedVertexMesh::empty();
+        let mut positions = DenseMap::new();
+        let [va, vb, vc, vd, ve, vf, vg, vh] = [(); 8].map(|_| {
+            let v = m.add_vertex();
+            positions.insert(v, Point3::new(0.0, 0.0, 0.0));
+            v
+        });
+        let small = m.add_triangle([ve, vf, vg]);
+        let f0 = m.add_triangle([va, vb, vc]);
+        let f1 = m.add_triangle([va, vc, vd]);
+
+        assert_eq!(connected_components(&m), [vec![small], vec![f0, f1]]);
+        assert_eq!(retain_largest_component(&mut m, &mut positions), (2, 1));
+        assert_eq!(m.face_handles().collect::<Vec<_>>(), [f0, f1]);
+        assert_eq!(m.vertex_handles().collect::<Vec<_>>(), [va, vb, vc, vd, vh]);
+        assert_eq!(positions.num_props(), 5);
+
+        assert_eq!(connected_components(&m), [vec![f0, f1]]);
+        assert_eq!(retain_largest_component(&mut m, &mut positions), (1, 0));
+    }
+}
@@ -0,0 +1,286 @@
+//! Algorithms for triangulating polygons.
+
+use lina::{Point2, Vec3, cross, dot, point2};
+
+use crate::{
+    prelude::*,
+    util::{PrimitiveFloat, Pos3Like},
+};
+use super::FaceProvenance;
+
+
+/// Triangulates a simple polygon via ear clipping and returns the triangles
+/// as index triples into `polygon`.
+///
+/// The polygon is given by its corners in order and may be concave. For
+/// polygons in 3D space, the corners are projected onto the best-fit plane
+/// (with the normal computed via Newell's method) before triangulating, so
+/// the polygon should be roughly planar. The returned triangles have the same
+/// orientation as the polygon. A polygon with `n >= 3` corners always results
+/// in `n - 2` triangles; polygons with fewer corners result in no triangles.
+///
+/// The polygon must not self-intersect. For degenerate input (e.g. all
+/// corners on a line), the result is still `n - 2` triangles, but they might
+/// be degenerate or overlapping.
+///
+/// # Example
+///
+/// ```
+/// use lox::algo::triangulation::ear_clip;
+/// use lina::Point3;
+///
+/// let quad = [
+///     Point3::new(0.0, 0.0, 0.0),
+///     Point3::new(1.0, 0.0, 0.0),
+///     Point3::new(1.0, 1.0, 0.0),
+///     Point3::new(0.0, 1.0, 0.0),
+/// ];
+/// assert_eq!(ear_clip(&quad).len(), 2);
+/// ```
+pub fn ear_clip<P, ScalarT>(polygon: &[P]) -> Vec<[usize; 3]>
+where
+    P: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let n = polygon.len();
+    if n < 3 {
+        return Vec::new();
+    }
+
+    // ----- Project the polygon into 2D ---------------------------------------
+    //
+    // Newell's method gives a robust normal even for concave and slightly
+    // non-planar polygons. Its direction corresponds to the polygon's
+    // orientation, so the projected polygon is always CCW.
+    let points = polygon.iter().map(|p| p.to_point3().to_vec()).collect::<Vec<_>>();
+    let normal = (0..n).fold(Vec3::zero(), |acc, i| acc + cross(points[i], points[(i + 1) % n]));
+
+    let projected = if normal == Vec3::zero() {
+        // Degenerate polygon: any projection is as good as any other.
+        points.iter().map(|p| point2(p.x, p.y)).collect::<Vec<_>>()
+    } else {
+        // Build an orthonormal basis `(u, v)` of the plane with `u × v`
+        // pointing in the direction of `normal`.
+        let normal = normal.normalized();
+        let helper = if normal.x.abs() < normal.y.abs() { Vec3::unit_x() } else { Vec3::unit_y() };
+        let u = cross(helper, normal).normalized();
+        let v = cross(normal, u);
+        points.iter().map(|&p| point2(dot(p, u), dot(p, v))).collect()
+    };
+
+    // ----- Clip ears ---------------------------------------------------------
+    let mut remaining = (0..n).collect::<Vec<_>>();
+    let mut out = Vec::with_capacity(n - 2);
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let corners = |i: usize| {
+            [remaining[(i + len - 1) % len], remaining[i], remaining[(i + 1) % len]]
+        };
+
+        let ear = (0..len).find(|&i| {
+            let [a, b, c] = corners(i);
+            let [pa, pb, pc] = [projected[a], projected[b], projected[c]];
+            is_convex(pa, pb, pc) && remaining.iter()
+                .filter(|&&x| x != a && x != b && x != c)
+                .all(|&x| !is_in_triangle(projected[x], pa, pb, pc))
+        });
+
+        // If there is no ear (which can only happen for degenerate or
+        // self-intersecting polygons), we still clip some corner to make
+        // progress.
+        let ear = ear.unwrap_or(0);
+        out.push(corners(ear));
+        remaining.remove(ear);
+    }
+
+    out.push([remaining[0], remaining[1], remaining[2]]);
+    out
+}
+
+/// Triangulates all non-triangular faces of the mesh and returns the
+/// provenance of all new triangles.
+///
+/// Each face with more than three vertices is removed and replaced by the
+/// triangles computed by [`ear_clip`] from the face's vertex positions. All
+/// new triangles are mapped to the handle of the face they replace in the
+/// returned map (note that this handle is invalid now). Triangular faces are
+/// not touched and are not contained in the returned map.
+///
+/// The given `vertex_positions` must have a position for every vertex of a
+/// non-triangular face or else this function panics.
+pub fn triangulate<MeshT, MapT, ScalarT>(
+    mesh: &mut MeshT,
+    vertex_positions: &MapT,
+) -> FaceProvenance
+where
+    MeshT: MeshMut + BasicAdj,
+    MapT: PropMap<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let mut provenance = FaceProvenance::new();
+    let faces = mesh.face_handles().collect::<Vec<_>>();
+    for fh in faces {
+        let vertices = mesh.vertices_around_face(fh).collect::<Vec<_>>();
+        if vertices.len() <= 3 {
+            continue;
+        }
+
+        let positions = vertices.iter()
+            .map(|&vh| *vertex_positions.get(vh).expect("missing vertex position"))
+            .collect::<Vec<_>>();
+
+        mesh.remove_face(fh);
+        for [a, b, c] in ear_clip(&positions) {
+            let new = mesh.add_triangle([vertices[a], vertices[b], vertices[c]]);
+            provenance.insert(new, Some(fh));
+        }
+    }
+
+    provenance
+}
+
+/// Returns the cross product of `b - a` and `c - a`, which is positive for
+/// CCW triangles.
+fn orientation<S: PrimitiveFloat>(a: Point2<S>, b: Point2<S>, c: Point2<S>) -> S {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn is_convex<S: PrimitiveFloat>(a: Point2<S>, b: Point2<S>, c: Point2<S>) -> bool {
+    orientation(a, b, c) > S::zero()
+}
+
+/// Checks whether `p` lies inside or on the border of the CCW triangle.
+fn is_in_triangle<S: PrimitiveFloat>(p: Point2<S>, a: Point2<S>, b: Point2<S>, c: Point2<S>) -> bool {
+    orientation(a, b, p) >= S::zero()
+        && orientation(b, c, p) >= S::zero()
+        && orientation(c, a, p) >= S::zero()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use lina::Point3;
+    use crate::{
+        core::half_edge::HalfEdgeMesh,
+        map::DenseMap,
+    };
+    use super::*;
+
+    /// Returns the sum of the triangles' areas, all signed with respect to
+    /// `normal`.
+    fn signed_area(polygon: &[Point3<f64>], triangles: &[[usize; 3]], normal: Vec3<f64>) -> f64 {
+        triangles.iter().map(|&[a, b, c]| {
+            let n = cross(polygon[b] - polygon[a], polygon[c] - polygon[a]);
+            dot(n, normal) / 2.0
+        }).sum()
+    }
+
+    fn check(polygon: &[Point3<f64>], normal: Vec3<f64>, expected_area: f64) {
+        let triangles = ear_clip(polygon);
+        assert_eq!(triangles.len(), polygon.len() - 2);
+
+        // Every triangle is CCW, so there is no overlap if the areas add up.
+        for &[a, b, c] in &triangles {
+            let n = cross(polygon[b] - polygon[a], polygon[c] - polygon[a]);
+            assert!(dot(n, normal) > 0.0, "flipped triangle {:?}", [a, b, c]);
+        }
+        assert!((signed_area(polygon, &triangles, normal) - expected_area).abs() < 1e-9);
+    }
+
+    #[test]
+    fn too_few_corners() {
+        assert!(ear_clip::<Point3<f64>, _>(&[]).is_empty());
+        assert!(ear_clip(&[Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0)]).is_empty());
+    }
+
+    #[test]
+    fn triangle() {
+        let tri = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ];
+        assert_eq!(ear_clip(&tri), [[0, 1, 2]]);
+    }
+
+    #[test]
+    fn convex_quad() {
+        let quad = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ];
+        check(&quad, Vec3::unit_z(), 2.0);
+    }
+
+    #[test]
+    fn concave_l_shape() {
+        //  5---4
+        //  |   |
+        //  |   3---2
+        //  |       |
+        //  0-------1
+        let l = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(1.0, 2.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+        ];
+        check(&l, Vec3::unit_z(), 3.0);
+
+        // The same polygon in reverse order is CW when looking from +z.
+        let reversed = l.iter().rev().copied().collect::<Vec<_>>();
+        check(&reversed, -Vec3::<f64>::unit_z(), 3.0);
+    }
+
+    #[test]
+    fn concave_l_shape_in_3d() {
+        // The L shape from above in the plane `x = 3`, seen from -x.
+        let l = [
+            Point3::new(3.0, 0.0, 0.0),
+            Point3::new(3.0, 0.0, 2.0),
+            Point3::new(3.0, 1.0, 2.0),
+            Point3::new(3.0, 1.0, 1.0),
+            Point3::new(3.0, 2.0, 1.0),
+            Point3::new(3.0, 2.0, 0.0),
+        ];
+        check(&l, -Vec3::<f64>::unit_x(), 3.0);
+    }
+
+    #[test]
+    fn triangulate_quad() {
+        //  (d)---(c)
+        //   |     | \
+        //   |  Q  | T (e)
+        //   |     | /
+        //  (a)---(b)
+        let mut mesh = <HalfEdgeMesh>::empty();
+        let mut positions = DenseMap::new();
+        let [va, vb, vc, vd, ve] = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(2.0, 0.5, 0.0),
+        ].map(|p| {
+            let vh = mesh.add_vertex();
+            positions.insert(vh, p);
+            vh
+        });
+        let quad = mesh.add_face(&[va, vb, vc, vd]);
+        let tri = mesh.add_triangle([vb, ve, vc]);
+
+        let provenance = triangulate(&mut mesh, &positions);
+        assert_eq!(mesh.num_faces(), 3);
+        assert_eq!(provenance.num_props(), 2);
+        assert!(!provenance.contains_handle(tri));
+        for fh in mesh.face_handles().filter(|&fh| fh != tri) {
+            assert_eq!(mesh.vertices_around_face(fh).count(), 3);
+            assert_eq!(provenance[fh], Some(quad));
+        }
+    }
+}
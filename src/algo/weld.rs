@@ -0,0 +1,207 @@
+//! Merging vertices with (almost) the same position.
+
+use std::collections::HashMap;
+
+use lina::{Point3, Vec3};
+use smallvec::SmallVec;
+
+use crate::{
+    cast,
+    prelude::*,
+    map::DenseMap,
+    util::{PrimitiveFloat, Pos3Like},
+};
+use super::bounding;
+
+
+/// Merges all vertices that are at most `eps` apart and returns the number of
+/// removed vertices.
+///
+/// Vertices are processed in ascending handle order: each vertex is merged
+/// into the first already kept vertex within `eps`, if any. Note that this is
+/// not transitive: a chain of vertices each `eps` apart is not collapsed into
+/// a single vertex. Faces referencing merged vertices are removed and added
+/// again (with new handles), unless the merge made them degenerate, in which
+/// case they are dropped. The positions of removed vertices are removed from
+/// `vertex_positions`.
+///
+/// Faces are re-added one by one, so the mesh must be able to represent all
+/// intermediate states. `SharedVertexMesh` can represent anything, other
+/// mesh types might panic if welding produces non-manifold configurations.
+/// Panics if `eps` is not positive.
+///
+/// To choose the tolerance relative to the size of the mesh, see
+/// [`weld_vertices_relative`].
+pub fn weld_vertices<MeshT, MapT, ScalarT>(
+    mesh: &mut MeshT,
+    vertex_positions: &mut MapT,
+    eps: ScalarT,
+) -> usize
+where
+    MeshT: TriMesh + MeshMut + BasicAdj,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    assert!(eps > ScalarT::zero(), "weld tolerance must be positive");
+
+    // Kept vertices are sorted into a grid with cells of size `eps`, so that
+    // all candidates of a vertex are in the 27 cells around its own cell.
+    let cell_of = |p: Point3<ScalarT>| {
+        [p.x, p.y, p.z].map(|c| cast::lossy::<_, i64>((c / eps).floor()))
+    };
+    let mut grid = HashMap::<[i64; 3], SmallVec<[VertexHandle; 2]>, ahash::RandomState>::default();
+    let mut merged_into = DenseMap::new();
+    for v in mesh.vertex_handles() {
+        let pos = vertex_positions[v].to_point3();
+        let [x, y, z] = cell_of(pos);
+        let target = (-1..=1)
+            .flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| [x + dx, y + dy, z + dz])))
+            .filter_map(|cell| grid.get(&cell))
+            .flatten()
+            .copied()
+            .filter(|&other| pos.distance_from(vertex_positions[other].to_point3()) <= eps)
+            .min();
+
+        match target {
+            Some(target) => {
+                merged_into.insert(v, target);
+            }
+            None => grid.entry([x, y, z]).or_default().push(v),
+        }
+    }
+
+    if merged_into.is_empty() {
+        return 0;
+    }
+
+    let affected_faces = mesh.face_handles()
+        .filter(|&f| {
+            mesh.vertices_around_triangle(f).iter().any(|&v| merged_into.contains_handle(v))
+        })
+        .collect::<Vec<_>>();
+    for f in affected_faces {
+        let vertices = mesh.vertices_around_triangle(f)
+            .map(|v| merged_into.get_ref(v).copied().unwrap_or(v));
+        mesh.remove_face(f);
+
+        let [a, b, c] = vertices;
+        if a != b && b != c && c != a {
+            mesh.add_triangle(vertices);
+        }
+    }
+
+    for (v, _) in merged_into.iter() {
+        mesh.remove_isolated_vertex(v);
+        vertex_positions.remove(v);
+    }
+
+    merged_into.num_props() as usize
+}
+
+/// Like [`weld_vertices`], but the tolerance is `relative_eps` times the
+/// length of the diagonal of the mesh's axis aligned bounding box. That makes
+/// the result independent of the scale of the mesh.
+///
+/// Does nothing and returns 0 if the mesh has no vertices or all vertices
+/// are at the same position.
+pub fn weld_vertices_relative<MeshT, MapT, ScalarT>(
+    mesh: &mut MeshT,
+    vertex_positions: &mut MapT,
+    relative_eps: ScalarT,
+) -> usize
+where
+    MeshT: TriMesh + MeshMut + BasicAdj,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like<Scalar = ScalarT>,
+    ScalarT: PrimitiveFloat,
+{
+    let bb = bounding::BoundingBox::around(mesh.vertex_handles().map(|v| vertex_positions[v]));
+    if !bb.is_valid() {
+        return 0;
+    }
+
+    let diagonal = Vec3::new(bb.x()[1] - bb.x()[0], bb.y()[1] - bb.y()[0], bb.z()[1] - bb.z()[0]);
+    let eps = relative_eps * diagonal.length();
+    if eps <= ScalarT::zero() {
+        return 0;
+    }
+
+    weld_vertices(mesh, vertex_positions, eps)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::core::SharedVertexMesh;
+    use super::*;
+
+    /// Builds an un-welded cube with side length `scale`: every triangle has
+    /// its own three vertices. Every other copy of a corner is displaced a
+    /// tiny bit.
+    fn cube_soup(scale: f64) -> (SharedVertexMesh, DenseMap<VertexHandle, Point3<f64>>) {
+        const TRIANGLES: [[usize; 3]; 12] = [
+            [0, 2, 1], [1, 2, 3], [4, 5, 6], [5, 7, 6],
+            [0, 1, 4], [1, 5, 4], [2, 6, 3], [3, 6, 7],
+            [0, 4, 2], [2, 4, 6], [1, 3, 5], [3, 7, 5],
+        ];
+        let corner = |i: usize| {
+            let offset = Vec3::new((i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64);
+            Point3::origin() + offset * scale
+        };
+
+        let mut mesh = SharedVertexMesh::empty();
+        let mut positions = DenseMap::new();
+        for (n, triangle) in TRIANGLES.iter().enumerate() {
+            let vertices = triangle.map(|i| {
+                let v = mesh.add_vertex();
+                let jitter = if n % 2 == 0 { 1e-9 * scale } else { 0.0 };
+                positions.insert(v, corner(i) + Vec3::new(jitter, 0.0, 0.0));
+                v
+            });
+            mesh.add_triangle(vertices);
+        }
+        (mesh, positions)
+    }
+
+    #[test]
+    fn weld_cube_soup() {
+        for scale in [1.0, 1000.0] {
+            let (mut mesh, mut positions) = cube_soup(scale);
+            assert_eq!(mesh.num_vertices(), 36);
+
+            assert_eq!(weld_vertices_relative(&mut mesh, &mut positions, 1e-6), 28);
+            mesh.check_integrity();
+            assert_eq!(mesh.num_vertices(), 8);
+            assert_eq!(mesh.num_faces(), 12);
+            assert_eq!(positions.num_props(), 8);
+            assert!(mesh.vertex_handles().all(|v| positions.contains_handle(v)));
+        }
+
+        // The same absolute tolerance only works for one of the scales.
+        let (mut mesh, mut positions) = cube_soup(1000.0);
+        assert!(weld_vertices(&mut mesh, &mut positions, 1e-7) < 28);
+        assert!(mesh.num_vertices() > 8);
+    }
+
+    #[test]
+    fn weld_drops_degenerate_faces() {
+        let mut mesh = SharedVertexMesh::empty();
+        let mut positions = DenseMap::new();
+        let mut add = |p: [f64; 3]| {
+            let v = mesh.add_vertex();
+            positions.insert(v, Point3::from(p));
+            v
+        };
+        let [va, vb, vc, vd] = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.05, 0.0]]
+            .map(&mut add);
+        mesh.add_triangle([va, vb, vc]);
+        mesh.add_triangle([va, vb, vd]);
+
+        assert_eq!(weld_vertices(&mut mesh, &mut positions, 0.1), 1);
+        mesh.check_integrity();
+        assert!(!mesh.contains_vertex(vd));
+        assert!(!positions.contains_handle(vd));
+        assert_eq!(mesh.num_faces(), 1);
+    }
+}
@@ -63,6 +63,10 @@
 //! makes more sense when considering these are numeric values and approximating
 //! during cast.
 //!
+//! `bool` also participates in casting: `bool` to any number is always
+//! lossless (`false` becomes 0, `true` becomes 1). The other direction is
+//! lossy: all non-zero values become `true`, only 0 becomes `false`.
+//!
 //!
 //! # Casting functions
 //!
@@ -75,6 +79,11 @@
 //! fidelity cannot be met. Remember: this function does *not* look at the
 //! value to decide whether `Some` or `None` is returned, but just at the types!
 //!
+//! The only exception to the "types only" rule is [`checked`], the
+//! value-dependent counterpart to the `try_*` family: it returns `None` if
+//! the specific value would be clamped (e.g. `300u16` to `u8`) and `Some`
+//! otherwise. It is only available for casts that never round.
+//!
 //!
 //! ## Example
 //!
@@ -104,9 +113,14 @@
 //! trait bounds for your own functions.
 //!
 //! `CastFrom` is implemented for all combination of primitive Rust types
-//! (unsigned integers, signed integers and floating point types).
+//! (unsigned integers, signed integers, floating point types and `bool`).
+//! Arrays `[T; N]` as well as `lina::Point` and `lina::Vector` are cast
+//! elementwise with the fidelity of their element cast, e.g.
+//! `cast::lossy::<[f64; 3], [f32; 3]>` or `cast::lossless::<Point3<u8>,
+//! Point3<f32>>`.
 //!
 
+use lina::{Point, Vector};
 use typebool::{Bool, True, False};
 
 use crate::sealed::Sealed;
@@ -211,6 +225,48 @@ where
 }
 
 
+/// Casts `src` from type `Src` to the type `Dst` if the value can be
+/// represented exactly by `Dst`, or returns `None` if it would be clamped.
+///
+/// Unlike all other functions in this module, this one looks at the value:
+/// `checked::<u16, u8>(200)` returns `Some(200)` while
+/// `checked::<u16, u8>(300)` returns `None`. Compare that to [`try_cast`]
+/// which returns `None` for both, as `u16` -> `u8` is not lossless for all
+/// values. Casts that might round (e.g. float to integer) are not supported
+/// and fail to compile, as the result would depend on the rounding mode.
+///
+/// ```
+/// use lox::cast;
+///
+/// assert_eq!(cast::checked::<u16, u8>(200), Some(200));
+/// assert_eq!(cast::checked::<u16, u8>(300), None);
+/// assert_eq!(cast::checked::<i32, u32>(-1), None);
+/// assert_eq!(cast::checked::<u8, f32>(7), Some(7.0));
+/// ```
+#[inline(always)]
+pub fn checked<Src, Dst>(src: Src) -> Option<Dst>
+where
+    Src: CastInto<Dst> + Copy + PartialEq,
+    Src::Fidelity: SufficientFor<Clamping>,
+    Dst: CastInto<Src> + Copy,
+{
+    // Lossless casts can always be performed. Handling them here also makes
+    // sure that NaN (which is not equal to itself) survives `f32` -> `f64`.
+    if is_cast_possible::<Lossless, Src, Dst>() {
+        return Some(src.cast_into());
+    }
+
+    // All remaining casts are between integers, where the value only changes
+    // if it was clamped. Clamped values don't survive the way back.
+    let dst: Dst = src.cast_into();
+    if dst.cast_into() == src {
+        Some(dst)
+    } else {
+        None
+    }
+}
+
+
 // ===========================================================================
 // ===== Casting fidelities
 // ===========================================================================
@@ -313,7 +369,10 @@ impl GreaterOrEqual<Lossy> for Lossy { type Out = True; }
 /// Ability to be casted from the type `Src`.
 ///
 /// This is the core trait of this module. It is implemented for all
-/// combinations of primitive types (12 x 12 = 144 impls).
+/// combinations of primitive number types (12 x 12 = 144 impls) plus casts
+/// from and to `bool`. For each of those, arrays and `lina` points and
+/// vectors of the element types can be cast elementwise, with the same
+/// fidelity.
 pub trait CastFrom<Src> {
     /// Fidelity with which this cast is performed.
     type Fidelity: Fidelity;
@@ -366,9 +425,37 @@ macro_rules! impl_cast {
                     impl_cast!(@imp $src -> $dst : $fidelity $(. $direction)?; src)
                 }
             }
+
+            impl<const N: usize> CastFrom<[$src; N]> for [$dst; N] {
+                type Fidelity = $fidelity;
+                fn cast_from(src: [$src; N]) -> Self {
+                    src.map(<$dst>::cast_from)
+                }
+            }
+
+            impl_cast!(@lina $src -> $dst : $fidelity);
         )*
     };
 
+    // `lina` types can't hold `bool`s.
+    (@lina bool -> $dst:ident : $fidelity:ident) => {};
+    (@lina $src:ident -> bool : $fidelity:ident) => {};
+    (@lina $src:ident -> $dst:ident : $fidelity:ident) => {
+        impl<const N: usize> CastFrom<Point<$src, N>> for Point<$dst, N> {
+            type Fidelity = $fidelity;
+            fn cast_from(src: Point<$src, N>) -> Self {
+                src.map(<$dst>::cast_from)
+            }
+        }
+
+        impl<const N: usize> CastFrom<Vector<$src, N>> for Vector<$dst, N> {
+            type Fidelity = $fidelity;
+            fn cast_from(src: Vector<$src, N>) -> Self {
+                src.map(<$dst>::cast_from)
+            }
+        }
+    };
+
     // The actual implementations on how it is cast. Compare the reference:
     // https://doc.rust-lang.org/reference/expressions/operator-expr.html#semantics
 
@@ -383,6 +470,10 @@ macro_rules! impl_cast {
     // these cases work as expected with the `as` cast operator.
     (@imp $src:ident -> $dst:ident : Lossy; $v:ident) => { $v as _ };
 
+    // Casting to `bool` is not possible with `as`. All non-zero values
+    // (including NaN) are mapped to `true`.
+    (@imp $src:ident -> bool : Lossy.nonzero; $v:ident) => { $v != 0 as $src };
+
     // Only clamping operations which only concern int -> int casts are a bit
     // more tricky. The `as` operator truncates instead of clamps. This might
     // make sense generally but not in the context of numeric operations. So we
@@ -563,6 +654,33 @@ impl_cast! {
     f64 ->  i64: Lossy,
     f64 -> i128: Lossy,
     f64 ->  f32: Lossy,
+
+    // ----- Bool ------------------------------------------------------------
+    bool ->   u8: Lossless,
+    bool ->  u16: Lossless,
+    bool ->  u32: Lossless,
+    bool ->  u64: Lossless,
+    bool -> u128: Lossless,
+    bool ->   i8: Lossless,
+    bool ->  i16: Lossless,
+    bool ->  i32: Lossless,
+    bool ->  i64: Lossless,
+    bool -> i128: Lossless,
+    bool ->  f32: Lossless,
+    bool ->  f64: Lossless,
+
+    u8   -> bool: Lossy.nonzero,
+    u16  -> bool: Lossy.nonzero,
+    u32  -> bool: Lossy.nonzero,
+    u64  -> bool: Lossy.nonzero,
+    u128 -> bool: Lossy.nonzero,
+    i8   -> bool: Lossy.nonzero,
+    i16  -> bool: Lossy.nonzero,
+    i32  -> bool: Lossy.nonzero,
+    i64  -> bool: Lossy.nonzero,
+    i128 -> bool: Lossy.nonzero,
+    f32  -> bool: Lossy.nonzero,
+    f64  -> bool: Lossy.nonzero,
 }
 
 
@@ -783,6 +901,50 @@ mod tests {
         assert_eq!(clamping::<i16, i8>(-20_000), -128);
     }
 
+    #[test]
+    fn cast_checked() {
+        assert_eq!(checked::<u16, u8>(0), Some(0));
+        assert_eq!(checked::<u16, u8>(200), Some(200));
+        assert_eq!(checked::<u16, u8>(255), Some(255));
+        assert_eq!(checked::<u16, u8>(256), None);
+        assert_eq!(checked::<u16, u8>(300), None);
+
+        assert_eq!(checked::<i16, u8>(-1), None);
+        assert_eq!(checked::<i16, i8>(-128), Some(-128));
+        assert_eq!(checked::<i16, i8>(-129), None);
+        assert_eq!(checked::<u8, i8>(127), Some(127));
+        assert_eq!(checked::<u8, i8>(128), None);
+        assert_eq!(checked::<i64, u32>(u32::MAX as i64), Some(u32::MAX));
+        assert_eq!(checked::<i64, u32>(u32::MAX as i64 + 1), None);
+        assert_eq!(checked::<u128, i128>(u128::MAX), None);
+
+        assert_eq!(checked::<u32, u32>(17), Some(17));
+        assert_eq!(checked::<u8, u64>(17), Some(17));
+        assert_eq!(checked::<i8, f64>(-3), Some(-3.0));
+        assert_eq!(checked::<bool, u8>(true), Some(1));
+        assert!(checked::<f32, f64>(f32::NAN).unwrap().is_nan());
+    }
+
+    #[test]
+    fn cast_composite() {
+        use lina::{Point3, Vec3};
+
+        assert_eq!(lossy::<[f64; 3], [f32; 3]>([1.5, -2.0, 0.25]), [1.5f32, -2.0, 0.25]);
+        assert_eq!(clamping::<[u16; 2], [u8; 2]>([3, 300]), [3, 255]);
+        assert_eq!(lossless::<[bool; 2], [u8; 2]>([true, false]), [1, 0]);
+        assert_eq!(try_cast::<Lossless, [i32; 3], [f32; 3]>([1, 2, 3]), None);
+        assert_eq!(checked::<[u16; 2], [u8; 2]>([3, 7]), Some([3, 7]));
+        assert_eq!(checked::<[u16; 2], [u8; 2]>([3, 300]), None);
+
+        assert_eq!(
+            lossless::<Point3<u8>, Point3<f64>>(Point3::new(1, 2, 3)),
+            Point3::new(1.0, 2.0, 3.0),
+        );
+        assert_eq!(lossy::<Vec3<f64>, Vec3<i32>>(Vec3::new(1.0, 2.0, -3.0)), Vec3::new(1, 2, -3));
+        assert!(!is_cast_possible::<Lossless, Point3<f64>, Point3<f32>>());
+        assert!(is_cast_possible::<Lossless, Point3<f32>, Point3<f64>>());
+    }
+
     #[test]
     fn cast_lossy() {
         assert_eq!(lossy::<f32, i8>(0.0), 0);
@@ -796,4 +958,46 @@ mod tests {
         // not specified.
         // TODO: rounding mode is now specified in the specs, add tests!
     }
+
+    #[test]
+    fn cast_from_bool() {
+        assert_eq!(lossless::<bool, u8>(true), 1);
+        assert_eq!(lossless::<bool, u16>(true), 1);
+        assert_eq!(lossless::<bool, u32>(true), 1);
+        assert_eq!(lossless::<bool, u64>(true), 1);
+        assert_eq!(lossless::<bool, u128>(true), 1);
+        assert_eq!(lossless::<bool, i8>(true), 1);
+        assert_eq!(lossless::<bool, i16>(true), 1);
+        assert_eq!(lossless::<bool, i32>(true), 1);
+        assert_eq!(lossless::<bool, i64>(true), 1);
+        assert_eq!(lossless::<bool, i128>(true), 1);
+        assert_eq!(lossless::<bool, f32>(true), 1.0);
+        assert_eq!(lossless::<bool, f64>(true), 1.0);
+
+        assert_eq!(lossless::<bool, u8>(false), 0);
+        assert_eq!(lossless::<bool, i64>(false), 0);
+        assert_eq!(lossless::<bool, f32>(false), 0.0);
+
+        assert_eq!(try_cast::<Lossless, bool, f64>(true), Some(1.0));
+        assert_eq!(try_cast::<SameType, bool, f64>(true), None);
+    }
+
+    #[test]
+    fn cast_to_bool() {
+        assert_eq!(lossy::<u8, bool>(0), false);
+        assert_eq!(lossy::<u8, bool>(1), true);
+        assert_eq!(lossy::<u16, bool>(300), true);
+        assert_eq!(lossy::<i32, bool>(-1), true);
+        assert_eq!(lossy::<i128, bool>(0), false);
+        assert_eq!(lossy::<f32, bool>(0.0), false);
+        assert_eq!(lossy::<f32, bool>(-0.0), false);
+        assert_eq!(lossy::<f32, bool>(0.25), true);
+        assert_eq!(lossy::<f64, bool>(f64::NAN), true);
+
+        assert_eq!(try_cast::<Lossless, u8, bool>(1), None);
+        assert_eq!(try_cast::<Clamping, u8, bool>(1), None);
+        assert_eq!(try_cast::<Rounding, f32, bool>(1.0), None);
+        assert_eq!(try_cast::<Lossy, u8, bool>(1), Some(true));
+        assert_eq!(try_cast::<SameType, bool, bool>(true), Some(true));
+    }
 }
@@ -74,3 +74,41 @@ impl<H: Handle> ops::Deref for Checked<H> {
         &self.0
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use std::mem::size_of;
+
+    use optional::{Noned, Optioned};
+
+    use crate::{hsize, Handle, EdgeHandle, FaceHandle, VertexHandle};
+    use super::Checked;
+
+    #[test]
+    fn hsize_matches_feature() {
+        #[cfg(not(feature = "large-handle"))]
+        assert_eq!(size_of::<hsize>(), 4);
+
+        #[cfg(feature = "large-handle")]
+        assert_eq!(size_of::<hsize>(), 8);
+    }
+
+    #[test]
+    fn optioned_has_handle_size() {
+        assert_eq!(size_of::<Optioned<Checked<VertexHandle>>>(), size_of::<hsize>());
+        assert_eq!(size_of::<Optioned<Checked<FaceHandle>>>(), size_of::<hsize>());
+        assert_eq!(size_of::<Optioned<Checked<EdgeHandle>>>(), size_of::<hsize>());
+    }
+
+    #[test]
+    fn none_sentinel_is_max_value() {
+        let none = Checked::<FaceHandle>::get_none();
+        assert!(none.is_none());
+        assert_eq!(none.idx(), hsize::max_value());
+
+        let largest = unsafe { Checked::new(FaceHandle::new(hsize::max_value() - 1)) };
+        assert!(!largest.is_none());
+        assert!(Optioned::some(largest).is_some());
+    }
+}
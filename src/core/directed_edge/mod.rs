@@ -591,17 +591,13 @@ impl<C: Config> Mesh for DirectedEdgeMesh<C> {
     }
 
     fn next_vertex_handle_from(&self, start: VertexHandle) -> Option<VertexHandle> {
-        // TODO: optimize
-        (start.idx()..self.vertices.next_push_handle().idx())
-            .map(VertexHandle::new)
-            .find(|&vh| self.vertices.contains_handle(vh))
+        self.vertices.next_handle_from(start)
     }
 
     fn next_face_handle_from(&self, start: FaceHandle) -> Option<FaceHandle> {
-        // TODO: optimize
-        (start.idx()..self.half_edges.next_push_handle().idx() / 3)
-            .map(|i| HalfEdgeHandle::new(i * 3))
-            .find(|&heh| self.half_edges.contains_handle(heh))
+        // The three half edges of a face are always added and removed
+        // together, so the first one found belongs to the next face.
+        self.half_edges.next_handle_from(HalfEdgeHandle::new(start.idx() * 3))
             .map(|he| he.face())
     }
 
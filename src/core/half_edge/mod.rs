@@ -969,17 +969,11 @@ impl<C: Config> Mesh for HalfEdgeMesh<C> {
     }
 
     fn next_vertex_handle_from(&self, start: VertexHandle) -> Option<VertexHandle> {
-        // TODO: optimize
-        (start.idx()..self.vertices.next_push_handle().idx())
-            .map(VertexHandle::new)
-            .find(|&vh| self.vertices.contains_handle(vh))
+        self.vertices.next_handle_from(start)
     }
 
     fn next_face_handle_from(&self, start: FaceHandle) -> Option<FaceHandle> {
-        // TODO: optimize
-        (start.idx()..self.faces.next_push_handle().idx())
-            .map(FaceHandle::new)
-            .find(|&fh| self.faces.contains_handle(fh))
+        self.faces.next_handle_from(start)
     }
 
     fn last_vertex_handle(&self) -> Option<VertexHandle> {
@@ -1013,19 +1007,10 @@ impl<C: Config> Mesh for HalfEdgeMesh<C> {
     where
         Self: EdgeMesh,
     {
-        // TODO: optimize
-        let mut idx = HalfEdgeHandle::lower_half_of(start).idx();
-        let end = self.half_edges.next_push_handle().idx();
-        while idx < end {
-            let he = HalfEdgeHandle::new(idx);
-            if self.half_edges.contains_handle(he) {
-                return Some(he.full_edge());
-            }
-
-            idx += 2;
-        }
-
-        None
+        // Both halves of an edge are always added and removed together, so
+        // the first one found belongs to the next edge.
+        self.half_edges.next_handle_from(HalfEdgeHandle::lower_half_of(start))
+            .map(|he| he.full_edge())
     }
 
     fn last_edge_handle(&self) -> Option<EdgeHandle>
@@ -180,6 +180,19 @@ impl FaceKind for PolyFaces {
 /// - [`Mesh::vertex_handles`]
 /// - [`Mesh::face_handles`]
 /// - [`Mesh::edge_handles`]
+///
+/// This iterator borrows the mesh, so the mesh cannot be modified while
+/// iterating. Use [`HandleIterMut`] if you need to do that.
+///
+/// ```compile_fail
+/// use lox::{prelude::*, core::SharedVertexMesh};
+///
+/// let mut mesh = SharedVertexMesh::empty();
+/// mesh.add_vertex();
+/// for _ in mesh.vertex_handles() {
+///     mesh.add_vertex();
+/// }
+/// ```
 #[derive(Debug, Clone)]
 pub struct HandleIter<'a, M: Mesh + ?Sized, H: Handle> {
     current: H,
@@ -0,0 +1,159 @@
+//! Undo/redo support for [`SharedVertexMesh`].
+
+use crate::{
+    hsize,
+    prelude::*,
+    map::DenseMap,
+};
+use super::SharedVertexMesh;
+
+
+/// A primitive change of a [`SharedVertexMesh`]. Every public mutating
+/// operation is recorded as a group of these.
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Op {
+    AddVertex(VertexHandle),
+    RemoveVertex(VertexHandle),
+    AddFace(FaceHandle, [VertexHandle; 3]),
+    RemoveFace(FaceHandle, [VertexHandle; 3]),
+}
+
+impl Op {
+    fn inverse(self) -> Self {
+        match self {
+            Op::AddVertex(v) => Op::RemoveVertex(v),
+            Op::RemoveVertex(v) => Op::AddVertex(v),
+            Op::AddFace(f, vertices) => Op::RemoveFace(f, vertices),
+            Op::RemoveFace(f, vertices) => Op::AddFace(f, vertices),
+        }
+    }
+
+    /// Applies this change to the raw maps of a mesh, keeping the per-vertex
+    /// face counts in sync.
+    fn apply(
+        self,
+        vertices: &mut DenseMap<VertexHandle, hsize>,
+        faces: &mut DenseMap<FaceHandle, [VertexHandle; 3]>,
+    ) {
+        match self {
+            Op::AddVertex(v) => {
+                vertices.insert(v, 0);
+            }
+            Op::RemoveVertex(v) => {
+                vertices.remove(v);
+            }
+            Op::AddFace(f, face_vertices) => {
+                for v in face_vertices {
+                    vertices[v] += 1;
+                }
+                faces.insert(f, face_vertices);
+            }
+            Op::RemoveFace(f, face_vertices) => {
+                for v in face_vertices {
+                    vertices[v] -= 1;
+                }
+                faces.remove(f);
+            }
+        }
+    }
+}
+
+/// The recorded history of a mesh, see [`SharedVertexMesh::begin_journal`].
+#[derive(Debug, Clone, Default)]
+pub(super) struct Journal {
+    undo: Vec<Vec<Op>>,
+    redo: Vec<Vec<Op>>,
+}
+
+impl SharedVertexMesh {
+    /// Starts recording all changes to this mesh, enabling
+    /// [`undo`][Self::undo] and [`redo`][Self::redo]. If the mesh is already
+    /// recording, its history is discarded and recording starts anew.
+    ///
+    /// Each call of a mutating method (from [`MeshMut`]) is one step that can
+    /// be undone. Handles are restored exactly: after undoing, the mesh
+    /// compares equal (`==`) to the mesh before the undone steps. The history
+    /// is only kept in memory and grows with every change until
+    /// [`end_journal`][Self::end_journal] is called.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lox::{core::SharedVertexMesh, prelude::*};
+    ///
+    /// let mut mesh = SharedVertexMesh::empty();
+    /// let [va, vb, vc] = [mesh.add_vertex(), mesh.add_vertex(), mesh.add_vertex()];
+    ///
+    /// mesh.begin_journal();
+    /// let f = mesh.add_triangle([va, vb, vc]);
+    /// mesh.remove_face(f);
+    ///
+    /// assert!(mesh.undo());
+    /// assert!(mesh.contains_face(f));
+    /// assert!(mesh.undo());
+    /// assert_eq!(mesh.num_faces(), 0);
+    /// assert!(!mesh.undo());
+    ///
+    /// assert!(mesh.redo());
+    /// assert!(mesh.contains_face(f));
+    /// ```
+    pub fn begin_journal(&mut self) {
+        self.journal = Some(Journal::default());
+    }
+
+    /// Stops recording changes and discards the history.
+    pub fn end_journal(&mut self) {
+        self.journal = None;
+    }
+
+    /// Reverts the last recorded step. Returns `false` (and does nothing) if
+    /// there is nothing to undo, including when no journal is active.
+    pub fn undo(&mut self) -> bool {
+        let Some(journal) = &mut self.journal else {
+            return false;
+        };
+        let Some(ops) = journal.undo.pop() else {
+            return false;
+        };
+
+        for &op in ops.iter().rev() {
+            op.inverse().apply(&mut self.vertices, &mut self.faces);
+        }
+        journal.redo.push(ops);
+        true
+    }
+
+    /// Re-applies the last undone step. Returns `false` (and does nothing) if
+    /// there is nothing to redo. Any new change to the mesh after an undo
+    /// clears the steps that could be redone.
+    pub fn redo(&mut self) -> bool {
+        let Some(journal) = &mut self.journal else {
+            return false;
+        };
+        let Some(ops) = journal.redo.pop() else {
+            return false;
+        };
+
+        for &op in &ops {
+            op.apply(&mut self.vertices, &mut self.faces);
+        }
+        journal.undo.push(ops);
+        true
+    }
+
+    /// Records one step, if a journal is active. `ops` is only called in
+    /// that case. Steps without any changes are not recorded.
+    pub(super) fn record(&mut self, ops: impl FnOnce(&Self) -> Vec<Op>) {
+        if self.journal.is_none() {
+            return;
+        }
+
+        let ops = ops(self);
+        if let Some(journal) = &mut self.journal {
+            if !ops.is_empty() {
+                journal.redo.clear();
+                journal.undo.push(ops);
+            }
+        }
+    }
+}
@@ -10,6 +10,9 @@ use crate::{
     map::DenseMap,
 };
 use super::{TriFaces, SplitEdgeWithFacesResult};
+use self::journal::{Journal, Op};
+
+mod journal;
 
 
 /// One of the simplest and the most used data structure for representing
@@ -19,16 +22,78 @@ use super::{TriFaces, SplitEdgeWithFacesResult};
 /// adjacency query: F → V (`BasicAdj`). This is sufficient for most kinds of
 /// rendering and to read and write meshes from/to files.
 ///
-/// The only thing this data structure stores are three vertex handles per
+/// The main thing this data structure stores are three vertex handles per
 /// face. This is very similar to to a "triangle list" index buffer of real
 /// time graphic pipelines (like OpenGL). As core meshes do not store
-/// properties, no data is stored per vertex.
+/// properties, the only thing stored per vertex is the number of faces using
+/// it, which allows `remove_isolated_vertex` to check its precondition.
 ///
 #[doc = include_str!("diagram.svg")]
 #[derive(Clone, Empty)]
 pub struct SharedVertexMesh {
-    vertices: DenseMap<VertexHandle, ()>,
+    /// The number of faces each vertex is part of.
+    vertices: DenseMap<VertexHandle, hsize>,
     faces: DenseMap<FaceHandle, [VertexHandle; 3]>,
+
+    /// Only `Some` while recording, see [`SharedVertexMesh::begin_journal`].
+    journal: Option<Journal>,
+}
+
+impl SharedVertexMesh {
+    /// Adds a polygon by splitting it into a fan of triangles around its first
+    /// vertex and returns the handle of the first triangle.
+    ///
+    /// This data structure only stores triangles, so [`MeshMut::add_face`] is
+    /// not available. A polygon with `n` vertices (in CCW order, like for
+    /// `add_face`) is added as `n - 2` triangles which get consecutive face
+    /// handles starting with the returned one. This works well for convex
+    /// polygons. For concave polygons, some triangles might overlap or be
+    /// flipped. If a journal is active, all triangles are recorded as a single
+    /// step.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `vertices.len() < 3`, if a vertex handle is
+    /// invalid or if a vertex appears more than once.
+    pub fn add_polygon(&mut self, vertices: &[VertexHandle]) -> FaceHandle {
+        assert!(vertices.len() >= 3, "polygon with fewer than three vertices");
+        for (i, &v) in vertices.iter().enumerate() {
+            assert!(self.vertices.contains_handle(v));
+            assert!(!vertices[..i].contains(&v), "vertices of new polygon are not unique");
+        }
+
+        let triangles = (1..vertices.len() - 1)
+            .map(|i| [vertices[0], vertices[i], vertices[i + 1]])
+            .collect::<Vec<_>>();
+        for &v in triangles.iter().flatten() {
+            self.vertices[v] += 1;
+        }
+
+        let faces = triangles.iter().map(|&t| self.faces.push(t)).collect::<Vec<_>>();
+        self.record(|_| faces.iter().zip(&triangles).map(|(&f, &t)| Op::AddFace(f, t)).collect());
+        faces[0]
+    }
+
+    /// Removes all given faces, like calling [`MeshMut::remove_face`] for
+    /// each of them. Handles of faces that do not exist (anymore) are
+    /// ignored.
+    ///
+    /// Removing a face is already `O(1)` as it only leaves a hole in the face
+    /// list, and the handles of all remaining faces stay valid. The
+    /// difference to removing faces one by one is that, if a journal is
+    /// active, the removal is recorded as a single step.
+    pub fn remove_faces(&mut self, faces: &[FaceHandle]) {
+        let mut ops = Vec::new();
+        for &f in faces {
+            if let Some(vertices) = self.faces.remove(f) {
+                for v in vertices {
+                    self.vertices[v] -= 1;
+                }
+                ops.push(Op::RemoveFace(f, vertices));
+            }
+        }
+        self.record(|_| ops);
+    }
 }
 
 impl Mesh for SharedVertexMesh {
@@ -41,18 +106,12 @@ impl Mesh for SharedVertexMesh {
 
     #[inline(always)]
     fn next_vertex_handle_from(&self, start: VertexHandle) -> Option<VertexHandle> {
-        // TODO: optimize
-        (start.idx()..self.vertices.next_push_handle().idx())
-            .map(VertexHandle::new)
-            .find(|&vh| self.vertices.contains_handle(vh))
+        self.vertices.next_handle_from(start)
     }
 
     #[inline(always)]
     fn next_face_handle_from(&self, start: FaceHandle) -> Option<FaceHandle> {
-        // TODO: optimize
-        (start.idx()..self.faces.next_push_handle().idx())
-            .map(FaceHandle::new)
-            .find(|&fh| self.faces.contains_handle(fh))
+        self.faces.next_handle_from(start)
     }
 
     fn last_vertex_handle(&self) -> Option<VertexHandle> {
@@ -96,12 +155,26 @@ impl Mesh for SharedVertexMesh {
                 panic!("bug: vertices of face {:?} are not unique: {:?}", f, [va, vb, vc]);
             }
         }
+
+        let mut actual_counts = DenseMap::with_capacity(self.vertices.num_elements());
+        for &v in self.faces.values().flatten() {
+            *actual_counts.get_or_insert(v, 0 as hsize) += 1;
+        }
+        for (v, &count) in self.vertices.iter() {
+            let actual = actual_counts.get_ref(v).copied().unwrap_or(0);
+            assert!(
+                count == actual,
+                "bug: face count of {:?} is {}, but it is used by {} faces", v, count, actual,
+            );
+        }
     }
 }
 
 impl MeshMut for SharedVertexMesh {
     fn add_vertex(&mut self) -> VertexHandle {
-        self.vertices.push(())
+        let v = self.vertices.push(0);
+        self.record(|_| vec![Op::AddVertex(v)]);
+        v
     }
 
     fn add_triangle(&mut self, [va, vb, vc]: [VertexHandle; 3]) -> FaceHandle {
@@ -111,18 +184,45 @@ impl MeshMut for SharedVertexMesh {
         assert_ne!(va, vb, "vertices of new face are not unique");
         assert_ne!(va, vc, "vertices of new face are not unique");
 
-        self.faces.push([va, vb, vc])
+        for v in [va, vb, vc] {
+            self.vertices[v] += 1;
+        }
+        let f = self.faces.push([va, vb, vc]);
+        self.record(|_| vec![Op::AddFace(f, [va, vb, vc])]);
+        f
     }
 
     fn remove_isolated_vertex(&mut self, vertex: VertexHandle) {
-        // Unfortunately, we cannot check if `vertex` is isolated. If it's not,
-        // this mesh is inconsistent, but it won't lead to memory unsafety as
-        // we never access `vertices` unchecked.
+        // Like removing from a map, removing a vertex that does not exist
+        // does nothing.
+        let Some(&num_faces) = self.vertices.get_ref(vertex) else {
+            return;
+        };
+        assert!(
+            num_faces == 0,
+            "{:?} is not isolated but was passed to `remove_isolated_vertex`",
+            vertex,
+        );
+
+        self.record(|_| vec![Op::RemoveVertex(vertex)]);
         self.vertices.remove(vertex);
     }
 
     fn remove_face(&mut self, face: FaceHandle) {
-        self.faces.remove(face);
+        self.record(|m| m.faces.get_ref(face).map(|&vs| Op::RemoveFace(face, vs)).into_iter().collect());
+        if let Some(vertices) = self.faces.remove(face) {
+            for v in vertices {
+                self.vertices[v] -= 1;
+            }
+        }
+    }
+
+    fn reserve_for_vertices(&mut self, count: hsize) {
+        self.vertices.reserve(count);
+    }
+
+    fn reserve_for_faces(&mut self, count: hsize) {
+        self.faces.reserve(count);
     }
 
     fn remove_all_vertices(&mut self) {
@@ -131,19 +231,36 @@ impl MeshMut for SharedVertexMesh {
             "call to `remove_all_vertices`, but there are faces in the mesh!",
         );
 
+        self.record(|m| m.vertices.handles().map(Op::RemoveVertex).collect());
         self.vertices.clear();
     }
 
     fn remove_all_faces(&mut self) {
+        self.record(|m| m.faces.iter().map(|(f, &vs)| Op::RemoveFace(f, vs)).collect());
         self.faces.clear();
+        for count in self.vertices.values_mut() {
+            *count = 0;
+        }
     }
 
     fn split_face(&mut self, f: FaceHandle) -> VertexHandle {
         let [va, vb, vc] = self.faces[f];
-        let center = self.add_vertex();
-        self.faces[f] = [va, vb, center];
-        self.faces.push([vb, vc, center]);
-        self.faces.push([vc, va, center]);
+        let center = self.vertices.push(3);
+        let new = [[va, vb, center], [vb, vc, center], [vc, va, center]];
+        self.faces[f] = new[0];
+        let f1 = self.faces.push(new[1]);
+        let f2 = self.faces.push(new[2]);
+        self.vertices[va] += 1;
+        self.vertices[vb] += 1;
+        self.vertices[vc] += 1;
+
+        self.record(|_| vec![
+            Op::RemoveFace(f, [va, vb, vc]),
+            Op::AddVertex(center),
+            Op::AddFace(f, new[0]),
+            Op::AddFace(f1, new[1]),
+            Op::AddFace(f2, new[2]),
+        ]);
 
         center
     }
@@ -177,7 +294,7 @@ impl SupportsMultiBlade for SharedVertexMesh {}
 
 impl fmt::Debug for SharedVertexMesh {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        struct VerticesDebug<'a>(&'a DenseMap<VertexHandle, ()>);
+        struct VerticesDebug<'a>(&'a DenseMap<VertexHandle, hsize>);
         impl fmt::Debug for VerticesDebug<'_> {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
                 f.debug_list().entries(self.0.handles()).finish()
@@ -191,9 +308,157 @@ impl fmt::Debug for SharedVertexMesh {
     }
 }
 
+/// Handle-sensitive equality: two meshes are equal if they contain exactly the
+/// same vertex handles and the same face handles with the same vertices (in
+/// the same order) per face. This is *not* a check for isomorphism: two meshes
+/// describing the same surface with different handles are not equal.
+impl PartialEq for SharedVertexMesh {
+    fn eq(&self, other: &Self) -> bool {
+        self.vertices.handles().eq(other.vertices.handles())
+            && self.faces.iter().eq(other.faces.iter())
+    }
+}
+
+impl Eq for SharedVertexMesh {}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
     gen_mesh_tests!(SharedVertexMesh: [TriMesh, BasicAdj, SupportsMultiBlade]);
+
+    #[test]
+    fn eq() {
+        let mut a = SharedVertexMesh::empty();
+        let va = a.add_vertex();
+        let vb = a.add_vertex();
+        let vc = a.add_vertex();
+        let vd = a.add_vertex();
+        a.add_triangle([va, vb, vc]);
+
+        let mut b = a.clone();
+        assert_eq!(a, b);
+
+        b.add_triangle([vb, vd, vc]);
+        assert_ne!(a, b);
+
+        // Same connectivity, but different vertex order in the face.
+        let mut c = SharedVertexMesh::empty();
+        let [va, vb, vc, _] = [c.add_vertex(), c.add_vertex(), c.add_vertex(), c.add_vertex()];
+        c.add_triangle([vb, vc, va]);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn remove_vertex_after_removing_its_faces() {
+        let mut m = SharedVertexMesh::empty();
+        let [va, vb, vc, vd] = [m.add_vertex(), m.add_vertex(), m.add_vertex(), m.add_vertex()];
+        let fx = m.add_triangle([va, vb, vc]);
+        let fy = m.add_triangle([vb, vd, vc]);
+
+        m.remove_face(fy);
+        m.remove_isolated_vertex(vd);
+        assert_eq!(m.num_vertices(), 3);
+        m.check_integrity();
+
+        m.remove_face(fx);
+        for v in [va, vb, vc] {
+            m.remove_isolated_vertex(v);
+        }
+        assert_eq!(m.num_vertices(), 0);
+
+        // Removing a vertex that is already gone does nothing.
+        m.remove_isolated_vertex(va);
+        assert_eq!(m.num_vertices(), 0);
+    }
+
+    #[test]
+    fn add_polygon() {
+        let mut m = SharedVertexMesh::empty();
+        let v = [(); 5].map(|_| m.add_vertex());
+        m.begin_journal();
+        let f = m.add_polygon(&v);
+        m.check_integrity();
+
+        let faces = m.face_handles().map(|f| m.vertices_around_triangle(f)).collect::<Vec<_>>();
+        assert_eq!(m.face_handles().next(), Some(f));
+        assert_eq!(faces, [[v[0], v[1], v[2]], [v[0], v[2], v[3]], [v[0], v[3], v[4]]]);
+
+        // All triangles are undone at once.
+        assert!(m.undo());
+        assert_eq!(m.num_faces(), 0);
+        m.check_integrity();
+    }
+
+    #[test]
+    #[should_panic(expected = "vertices of new polygon are not unique")]
+    fn add_polygon_duplicate_vertex() {
+        let mut m = SharedVertexMesh::empty();
+        let [va, vb, vc] = [m.add_vertex(), m.add_vertex(), m.add_vertex()];
+        m.add_polygon(&[va, vb, vc, vb]);
+    }
+
+    #[test]
+    fn remove_faces() {
+        let mut m = SharedVertexMesh::empty();
+        let v = [(); 102].map(|_| m.add_vertex());
+        let faces = (0..100).map(|i| m.add_triangle([v[i], v[i + 1], v[i + 2]])).collect::<Vec<_>>();
+        let before = m.clone();
+
+        m.begin_journal();
+        let (odd, even) = faces.iter().partition::<Vec<_>, _>(|f| f.idx() % 2 == 1);
+        m.remove_faces(&odd);
+        m.remove_faces(&odd);
+        m.check_integrity();
+        assert_eq!(m.face_handles().collect::<Vec<_>>(), even);
+        for &f in &even {
+            assert_eq!(m.vertices_around_triangle(f), before.vertices_around_triangle(f));
+        }
+
+        // The first call was one step, the second one did nothing.
+        assert!(m.undo());
+        assert!(!m.undo());
+        assert_eq!(m, before);
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut m = SharedVertexMesh::empty();
+        let [va, vb, vc] = [m.add_vertex(), m.add_vertex(), m.add_vertex()];
+        let fx = m.add_triangle([va, vb, vc]);
+        let before = m.clone();
+
+        m.begin_journal();
+        let vd = m.add_vertex();
+        m.add_triangle([vb, vd, vc]);
+        m.split_face(fx);
+        m.remove_face(fx);
+        let after = m.clone();
+        m.check_integrity();
+
+        for _ in 0..4 {
+            assert!(m.undo());
+            m.check_integrity();
+        }
+        assert!(!m.undo());
+        assert_eq!(m, before);
+
+        for _ in 0..4 {
+            assert!(m.redo());
+        }
+        assert!(!m.redo());
+        assert_eq!(m, after);
+        m.check_integrity();
+
+        // A new change after undoing discards the redo history.
+        assert!(m.undo());
+        m.remove_all_faces();
+        assert!(!m.redo());
+        assert!(m.undo());
+        assert_eq!(m.num_faces(), 4);
+        m.check_integrity();
+
+        m.end_journal();
+        assert!(!m.undo());
+    }
 }
@@ -1454,39 +1454,67 @@ macro_rules! gen_mesh_tests {
             }
         });
 
-        test_helper!(@if_item [FullAdj] in $extras => {
-            #[test]
-            fn panic_on_remove_non_isolated_vertex() {
-                //
-                //  (e)      (a) ----- (b)        (e)      (a) ----- (b)
-                //          /   \  Y  /                       \  Y  /
-                //         /  X  \   /        =>               \   /
-                //        /       \ /                           \ /
-                //      (c) ----- (d)                 (c)       (d)
-                //
-                let mut m = <$name>::empty();
-                let va = m.add_vertex();
-                let vb = m.add_vertex();
-                let vc = m.add_vertex();
-                let vd = m.add_vertex();
-                m.add_vertex();
+        #[test]
+        fn panic_on_remove_non_isolated_vertex() {
+            //
+            //  (e)      (a) ----- (b)        (e)      (a) ----- (b)
+            //          /   \  Y  /                       \  Y  /
+            //         /  X  \   /        =>               \   /
+            //        /       \ /                           \ /
+            //      (c) ----- (d)                 (c)       (d)
+            //
+            let mut m = <$name>::empty();
+            let va = m.add_vertex();
+            let vb = m.add_vertex();
+            let vc = m.add_vertex();
+            let vd = m.add_vertex();
+            m.add_vertex();
 
-                m.add_triangle([va, vc, vd]);
-                m.add_triangle([va, vd, vb]);
+            m.add_triangle([va, vc, vd]);
+            m.add_triangle([va, vd, vb]);
 
-                let mut clone = m.clone();
-                assert_panic!(clone.remove_isolated_vertex(va));
+            let mut clone = m.clone();
+            assert_panic!(clone.remove_isolated_vertex(va));
 
-                let mut clone = m.clone();
-                assert_panic!(clone.remove_isolated_vertex(vb));
+            let mut clone = m.clone();
+            assert_panic!(clone.remove_isolated_vertex(vb));
 
-                let mut clone = m.clone();
-                assert_panic!(clone.remove_isolated_vertex(vc));
+            let mut clone = m.clone();
+            assert_panic!(clone.remove_isolated_vertex(vc));
 
-                let mut clone = m.clone();
-                assert_panic!(clone.remove_isolated_vertex(vd));
-            }
-        });
+            let mut clone = m.clone();
+            assert_panic!(clone.remove_isolated_vertex(vd));
+        }
+
+        #[test]
+        fn handles_in_ascending_order() {
+            //
+            //   (b) ----- (c)
+            //    | \  Y  / |
+            //    |  \   /  |
+            //    | X \ / Z |
+            //   (a) - (m) - (d)
+            //
+            let mut m = <$name>::empty();
+            let vm = m.add_vertex();
+            let va = m.add_vertex();
+            let vb = m.add_vertex();
+            let vc = m.add_vertex();
+            let vd = m.add_vertex();
+            m.add_triangle([vm, vb, va]);
+            let fy = m.add_triangle([vm, vc, vb]);
+            m.add_triangle([vm, vd, vc]);
+
+            m.remove_face(fy);
+            m.add_triangle([vm, vc, vb]);
+
+            let faces = m.face_handles().collect::<Vec<_>>();
+            assert_eq!(faces.len(), 3);
+            assert!(faces.windows(2).all(|w| w[0] < w[1]), "not ascending: {:?}", faces);
+
+            let vertices = m.vertex_handles().collect::<Vec<_>>();
+            assert_eq!(vertices, [vm, va, vb, vc, vd]);
+        }
 
         test_helper!(@if_item [TriMesh, EdgeMesh, FullAdj] in $extras => {
             #[test]
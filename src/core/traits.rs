@@ -167,8 +167,10 @@ pub trait Mesh: Empty + fmt::Debug {
     /// over `VertexRef`s, use [`vertices()`][Mesh::vertices], which is often
     /// more useful.
     ///
-    /// The order of these vertices is unspecified, but each vertex is yielded
-    /// by the iterator exactly once.
+    /// Vertices are yielded in ascending order of their handle's index and
+    /// each vertex is yielded by the iterator exactly once. The order is thus
+    /// stable: iterating the same unmodified mesh twice yields the same
+    /// sequence.
     fn vertex_handles(&self) -> HandleIter<'_, Self, VertexHandle> {
         HandleIter::<Self, VertexHandle>::new(self)
     }
@@ -203,8 +205,9 @@ pub trait Mesh: Empty + fmt::Debug {
     /// over `VertexRef`s, use [`faces()`][Mesh::faces], which is often more
     /// useful.
     ///
-    /// The order of these faces is unspecified, but each vertex is yielded by
-    /// the iterator exactly once.
+    /// Faces are yielded in ascending order of their handle's index and each
+    /// face is yielded by the iterator exactly once (see
+    /// [`vertex_handles()`][Mesh::vertex_handles]).
     fn face_handles(&self) -> HandleIter<'_, Self, FaceHandle> {
         HandleIter::<Self, FaceHandle>::new(self)
     }
@@ -241,8 +244,9 @@ pub trait Mesh: Empty + fmt::Debug {
     /// over `EdgeRef`s, use [`edges()`][Mesh::edges], which is often more
     /// useful.
     ///
-    /// The order of the edges is unspecified, but each edge is yielded by the
-    /// iterator exactly once.
+    /// Edges are yielded in ascending order of their handle's index and each
+    /// edge is yielded by the iterator exactly once (see
+    /// [`vertex_handles()`][Mesh::vertex_handles]).
     fn edge_handles(&self) -> HandleIter<'_, Self, EdgeHandle>
     where
         Self: EdgeMesh,
@@ -176,6 +176,8 @@
 //!
 //! - `large-handle`: makes the crate us 64 bit integers instead of 32 bit
 //!   integers for handles.
+//! - `rayon`: enables parallel versions of some operations, like
+//!   `DenseMap::par_map_value`.
 //!
 
 
@@ -203,7 +205,9 @@ pub mod algo;
 pub mod cast;
 pub mod core;
 pub mod map;
+pub mod math;
 pub mod prelude;
+pub mod shape;
 pub mod util;
 
 mod refs;
@@ -288,7 +292,8 @@ pub(crate) mod sealed {
 ///
 /// Of course, we would like to avoid annoying bugs due to errors like "use
 /// after free". The crate `slotmap` has really great ideas regarding this.
-/// `lox will try out some ideas to avoid some common mistakes in the future.
+/// As a first step, [`map::GenDenseMap`] offers generational handles which
+/// detect such mistakes, at the cost of a bit of memory and speed.
 ///
 /// # The size of `hsize`
 ///
@@ -1,4 +1,5 @@
-//! Helper types to implement [`PropMap::map`] and other adaptor functions.
+//! Helper types to implement [`PropMap::map`], [`PropMap::zip`],
+//! [`PropMap::filter`] and other adaptor functions.
 
 use crate::Handle;
 use super::{PropMap, Value};
@@ -26,3 +27,63 @@ where
             .map(|v| (&self.mapper)(v).into())
     }
 }
+
+
+/// Helper type for [`PropMap::zip`]. See that method for more information.
+#[derive(Debug)]
+pub struct Zip<'m, A, B> {
+    pub(super) first: &'m A,
+    pub(super) second: &'m B,
+}
+
+impl<H, A, B> PropMap<H> for Zip<'_, A, B>
+where
+    H: Handle,
+    A: PropMap<H>,
+    B: PropMap<H>,
+    A::Target: Clone,
+    B::Target: Clone,
+{
+    type Target = (A::Target, B::Target);
+    type Ret<'s> = Self::Target where Self: 's;
+
+    fn get(&self, handle: H) -> Option<Value<Self::Ret<'_>, Self::Target>> {
+        let a = self.first.get(handle)?;
+        let b = self.second.get(handle)?;
+        Some(((*a).clone(), (*b).clone()).into())
+    }
+
+    fn contains_handle(&self, handle: H) -> bool {
+        self.first.contains_handle(handle) && self.second.contains_handle(handle)
+    }
+}
+
+
+/// Helper type for [`PropMap::filter`]. See that method for more information.
+#[derive(Debug)]
+pub struct Filter<'m, M, F> {
+    pub(super) inner: &'m M,
+    pub(super) predicate: F,
+}
+
+impl<'m, H, M, F> PropMap<H> for Filter<'m, M, F>
+where
+    H: Handle,
+    M: PropMap<H>,
+    F: Fn(H) -> bool,
+{
+    type Target = M::Target;
+    type Ret<'s> = M::Ret<'m> where Self: 's;
+
+    fn get(&self, handle: H) -> Option<Value<Self::Ret<'_>, Self::Target>> {
+        if (self.predicate)(handle) {
+            self.inner.get(handle)
+        } else {
+            None
+        }
+    }
+
+    fn contains_handle(&self, handle: H) -> bool {
+        (self.predicate)(handle) && self.inner.contains_handle(handle)
+    }
+}
@@ -0,0 +1,257 @@
+use std::fmt;
+
+use crate::{
+    hsize,
+    prelude::*,
+};
+use super::{DenseMap, Handles, PropMap, PropStore, Value, set::DenseSet};
+
+
+/// A map from handles to `bool`, storing only a single bit per handle.
+///
+/// This is well suited for per-element flags like "visited", "selected" or
+/// "boundary", which are very common in traversal algorithms. Compared to a
+/// `DenseMap<H, bool>`, this uses a fraction of the memory.
+///
+/// This is a thin wrapper around a [`DenseSet`]: the bit of a handle is set
+/// if and only if the handle is in that set. So memory requirements and speed
+/// are the same as for `DenseSet` (and [`DenseMap`]). Use this type instead
+/// of `DenseSet` if you prefer thinking in flags or need a [`PropMap`].
+///
+/// As a [`PropMap`], this map is complete: `get` returns `Some(false)` for
+/// all handles whose bit was never set.
+///
+///
+/// # Example
+///
+/// ```
+/// use lox::{
+///     FaceHandle, Handle,
+///     map::{DenseBitSet, PropMap},
+/// };
+///
+/// let f0 = FaceHandle::from_usize(0);
+/// let f7 = FaceHandle::from_usize(7);
+///
+/// let mut visited = DenseBitSet::new();
+/// visited.set(f7);
+///
+/// assert!(visited.is_set(f7));
+/// assert!(!visited.is_set(f0));
+/// assert_eq!(visited.get(f0).map(|v| *v), Some(false));
+/// assert_eq!(visited.iter_set_handles().collect::<Vec<_>>(), [f7]);
+/// ```
+#[derive(Clone)]
+pub struct DenseBitSet<H: Handle> {
+    set: DenseSet<H>,
+}
+
+impl<H: Handle> DenseBitSet<H> {
+    /// Creates an empty bit set (all bits are unset).
+    pub fn new() -> Self {
+        Self {
+            set: DenseSet::empty(),
+        }
+    }
+
+    /// Creates an empty bit set with pre-allocated memory for handles with
+    /// indices below `cap`.
+    pub fn with_capacity(cap: hsize) -> Self {
+        Self {
+            set: DenseSet::with_capacity(cap),
+        }
+    }
+
+    /// Returns `true` if the bit for `handle` is set.
+    pub fn is_set(&self, handle: H) -> bool {
+        self.set.contains_handle(handle)
+    }
+
+    /// Sets the bit for `handle`. Returns `true` if the bit was already set
+    /// before, `false` otherwise.
+    pub fn set(&mut self, handle: H) -> bool {
+        self.set.insert(handle)
+    }
+
+    /// Unsets the bit for `handle`. Returns `true` if the bit was set before,
+    /// `false` otherwise.
+    pub fn clear(&mut self, handle: H) -> bool {
+        self.set.remove(handle)
+    }
+
+    /// Flips the bit for `handle` and returns its new value.
+    pub fn toggle(&mut self, handle: H) -> bool {
+        if self.clear(handle) {
+            false
+        } else {
+            self.set(handle);
+            true
+        }
+    }
+
+    /// Sets the bit for `handle` to the given `value`. Returns the old value.
+    pub fn set_to(&mut self, handle: H, value: bool) -> bool {
+        if value {
+            self.set(handle)
+        } else {
+            self.clear(handle)
+        }
+    }
+
+    /// Unsets all bits.
+    pub fn clear_all(&mut self) {
+        self.set.clear();
+    }
+
+    /// Returns the number of handles whose bit is set.
+    pub fn num_set(&self) -> hsize {
+        self.set.num_elements()
+    }
+
+    /// Returns an iterator over all handles whose bit is set, in ascending
+    /// order.
+    pub fn iter_set_handles(&self) -> SetHandles<'_, H> {
+        SetHandles(self.set.handles())
+    }
+
+    /// Returns the underlying set containing all handles whose bit is set.
+    pub fn as_set(&self) -> &DenseSet<H> {
+        &self.set
+    }
+
+    /// Converts this into the underlying set containing all handles whose bit
+    /// is set.
+    pub fn into_set(self) -> DenseSet<H> {
+        self.set
+    }
+}
+
+impl<H: Handle> PropMap<H> for DenseBitSet<H> {
+    type Target = bool;
+    type Ret<'s> = bool;
+
+    fn get(&self, handle: H) -> Option<Value<Self::Ret<'_>, Self::Target>> {
+        Some(self.is_set(handle).into())
+    }
+}
+
+impl<H: Handle> From<DenseSet<H>> for DenseBitSet<H> {
+    fn from(set: DenseSet<H>) -> Self {
+        Self { set }
+    }
+}
+
+impl<H: Handle> Default for DenseBitSet<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: Handle> Empty for DenseBitSet<H> {
+    fn empty() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: Handle> fmt::Debug for DenseBitSet<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter_set_handles()).finish()
+    }
+}
+
+impl<H: Handle> Extend<H> for DenseBitSet<H> {
+    fn extend<I: IntoIterator<Item = H>>(&mut self, iter: I) {
+        for handle in iter {
+            self.set(handle);
+        }
+    }
+}
+
+impl<H: Handle> FromIterator<H> for DenseBitSet<H> {
+    fn from_iter<I: IntoIterator<Item = H>>(iter: I) -> Self {
+        let mut out = Self::new();
+        out.extend(iter);
+        out
+    }
+}
+
+
+/// Iterator over all handles whose bit is set in a [`DenseBitSet`]. Returned
+/// by [`DenseBitSet::iter_set_handles`].
+#[derive(Debug)]
+pub struct SetHandles<'a, H: Handle>(Handles<<DenseMap<H, ()> as PropStore<H>>::Iter<'a>>);
+
+impl<H: Handle> Iterator for SetHandles<'_, H> {
+    type Item = H;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::FaceHandle;
+    use super::*;
+
+    fn h(x: usize) -> FaceHandle {
+        FaceHandle::from_usize(x)
+    }
+
+    #[test]
+    fn empty() {
+        let set = DenseBitSet::<FaceHandle>::new();
+        assert!(!set.is_set(h(0)));
+        assert!(!set.is_set(h(1000)));
+        assert_eq!(set.get(h(3)).map(|v| *v), Some(false));
+        assert_eq!(set.num_set(), 0);
+        assert_eq!(set.iter_set_handles().count(), 0);
+    }
+
+    #[test]
+    fn set_clear_toggle() {
+        let mut set = DenseBitSet::new();
+        assert!(!set.set(h(3)));
+        assert!(set.set(h(3)));
+        assert!(!set.set(h(64)));
+        assert!(!set.set(h(130)));
+
+        assert!(set.is_set(h(3)));
+        assert!(set.is_set(h(64)));
+        assert!(set.is_set(h(130)));
+        assert!(!set.is_set(h(4)));
+        assert!(!set.is_set(h(63)));
+        assert_eq!(set.num_set(), 3);
+
+        assert!(set.clear(h(64)));
+        assert!(!set.clear(h(64)));
+        assert!(!set.clear(h(5000)));
+        assert!(!set.is_set(h(64)));
+
+        assert!(set.toggle(h(0)));
+        assert!(set.is_set(h(0)));
+        assert!(!set.toggle(h(0)));
+        assert!(!set.is_set(h(0)));
+
+        assert!(!set.set_to(h(7), true));
+        assert!(set.set_to(h(7), false));
+        assert!(!set.is_set(h(7)));
+
+        set.clear_all();
+        assert_eq!(set.num_set(), 0);
+        assert!(!set.is_set(h(3)));
+    }
+
+    #[test]
+    fn iter_set_handles() {
+        let handles = [h(0), h(1), h(63), h(64), h(200), h(255), h(256)];
+        let set = handles.iter().copied().collect::<DenseBitSet<_>>();
+        assert_eq!(set.iter_set_handles().collect::<Vec<_>>(), handles);
+        assert_eq!(set.num_set(), handles.len() as hsize);
+
+        let set = DenseBitSet::from(set.into_set());
+        assert!(handles.iter().all(|&h| set.as_set().contains_handle(h)));
+        assert_eq!(set.as_set().num_elements(), handles.len() as hsize);
+    }
+}
@@ -2,6 +2,7 @@ use std::{
     fmt,
     iter::FromIterator,
     marker::PhantomData,
+    mem,
     ops::{Index, IndexMut},
 };
 
@@ -93,8 +94,19 @@ impl<H: Handle, T> DenseMap<H, T> {
         H::from_usize(self.vec.push(elem))
     }
 
-    pub(crate) fn next_push_handle(&self) -> H {
-        H::from_usize(self.vec.next_push_index())
+    /// Returns the smallest handle greater than or equal to `start` that has
+    /// a value associated with it.
+    ///
+    /// This is what the mesh data structures use to iterate over their
+    /// elements. Iterating over all handles with this method takes time
+    /// proportional to the highest handle, not to the number of elements.
+    pub(crate) fn next_handle_from(&self, start: H) -> Option<H> {
+        let start = start.to_usize();
+        if start >= self.vec.next_push_index() {
+            return None;
+        }
+
+        self.vec.first_filled_slot_from(start).map(H::from_usize)
     }
 
     pub(crate) fn last_handle(&self) -> Option<H> {
@@ -112,6 +124,71 @@ impl<H: Handle, T> DenseMap<H, T> {
     pub unsafe fn get_unchecked_mut(&mut self, handle: H) -> &mut T {
         self.vec.get_unchecked_mut(handle.to_usize())
     }
+
+    /// Returns the index of `handle` in the underlying vector when inserting.
+    ///
+    /// Panics with a clear message if the vector cannot grow to hold that
+    /// index on this platform: either the index does not fit into `usize`
+    /// (e.g. with `large-handle` on 32-bit platforms) or the allocation would
+    /// exceed `isize::MAX` bytes.
+    fn index_for_insert(handle: H) -> usize {
+        let too_large = || -> ! {
+            panic!("handle index of '{:?}' too large for this platform", handle);
+        };
+
+        let idx = usize::try_from(handle.idx()).unwrap_or_else(|_| too_large());
+        let bytes = idx.checked_add(1).and_then(|len| len.checked_mul(mem::size_of::<T>()));
+        match bytes {
+            Some(bytes) if bytes <= isize::MAX as usize => idx,
+            _ => too_large(),
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<H: Handle + Send + Sync, T: Sync> DenseMap<H, T> {
+    /// Applies `f` to all properties in parallel and returns a new map with
+    /// the results, associated with the same handles.
+    ///
+    /// This is the parallel version of `iter().map(..).collect()`. Only
+    /// available with the `rayon` feature.
+    pub fn par_map_value<U, F>(&self, f: F) -> DenseMap<H, U>
+    where
+        U: Send,
+        F: Fn(H, &T) -> U + Send + Sync,
+    {
+        let len = self.vec.next_push_index();
+        DenseMap::par_from_fn(len, |handle| self.get_ref(handle).map(|value| f(handle, value)))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<H: Handle + Send + Sync, T: Send> DenseMap<H, T> {
+    /// Calls `f` in parallel for the handles with the indices `0..len` and
+    /// returns a map with all `Some` results.
+    ///
+    /// The results are collected in index order and then moved into storage
+    /// that is allocated once for all `len` slots.
+    pub(crate) fn par_from_fn<F>(len: usize, f: F) -> Self
+    where
+        F: Fn(H) -> Option<T> + Send + Sync,
+    {
+        use rayon::prelude::*;
+
+        let mut results = Vec::with_capacity(len);
+        (0..len).into_par_iter().map(|i| f(H::from_usize(i))).collect_into_vec(&mut results);
+
+        let mut out = Self {
+            vec: StableVec::with_capacity(len),
+            _dummy: PhantomData,
+        };
+        for (idx, value) in results.into_iter().enumerate() {
+            if let Some(value) = value {
+                out.vec.insert(idx, value);
+            }
+        }
+        out
+    }
 }
 
 impl<H: Handle, T: Clone> DenseMap<H, T> {
@@ -187,7 +264,7 @@ impl<H: Handle, T> PropStoreMut<H> for DenseMap<H, T> {
     }
 
     fn insert(&mut self, handle: H, elem: Self::Output) -> Option<Self::Output> {
-        let idx = handle.to_usize();
+        let idx = Self::index_for_insert(handle);
         self.vec.reserve_for(idx);
         self.vec.insert(idx, elem)
     }
@@ -277,4 +354,58 @@ mod tests {
     use super::*;
 
     gen_tests_for_store_impl!(DenseMap);
+
+    #[test]
+    fn next_handle_from() {
+        let h = FaceHandle::from_usize;
+        let mut m = DenseMap::new();
+        assert_eq!(m.next_handle_from(h(0)), None);
+
+        for i in 0..100 {
+            m.insert(h(i), i);
+        }
+        for i in 1..99 {
+            m.remove(h(i));
+        }
+        m.reserve(50);
+
+        assert_eq!(m.next_handle_from(h(0)), Some(h(0)));
+        assert_eq!(m.next_handle_from(h(1)), Some(h(99)));
+        assert_eq!(m.next_handle_from(h(99)), Some(h(99)));
+        assert_eq!(m.next_handle_from(h(100)), None);
+        assert_eq!(m.next_handle_from(h(1000)), None);
+    }
+
+    #[test]
+    fn index_for_insert() {
+        let h = FaceHandle::new(hsize::max_value() - 1);
+        assert_eq!(DenseMap::<FaceHandle, u8>::index_for_insert(FaceHandle::new(5)), 5);
+        assert_eq!(DenseMap::<FaceHandle, ()>::index_for_insert(h), h.to_usize());
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    #[should_panic(expected = "too large for this platform")]
+    fn index_for_insert_overflow() {
+        // No value of this type is ever created, we just need a type large
+        // enough that `hsize::MAX` of them exceed `isize::MAX` bytes.
+        type Huge = [u8; 1 << 32];
+        DenseMap::<FaceHandle, Huge>::index_for_insert(FaceHandle::new(hsize::max_value() - 1));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_map_value() {
+        let mut m = DenseMap::new();
+        m.insert(FaceHandle::from_usize(0), 3);
+        m.insert(FaceHandle::from_usize(2), 5);
+        m.insert(FaceHandle::from_usize(7), 8);
+
+        let mut mapped = m.par_map_value(|h, &v| v * 10 + h.idx());
+        crate::map::tests::check(&mut mapped, &mut [
+            (FaceHandle::from_usize(0), 30),
+            (FaceHandle::from_usize(2), 52),
+            (FaceHandle::from_usize(7), 87),
+        ]);
+    }
 }
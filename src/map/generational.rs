@@ -0,0 +1,266 @@
+//! Generational handles, for catching use of handles whose element was
+//! removed.
+//!
+//! Plain handles are just indices. After an element is removed, its index can
+//! be reused for a new element, and a handle to the old element silently
+//! refers to the new one. [`GenDenseMap`] stores a generation counter per
+//! slot which is incremented whenever a value is removed. The
+//! [`GenHandle`]s it hands out remember the generation at the time of
+//! insertion, so stale handles are detected instead of returning the wrong
+//! value.
+//!
+//! This is opt-in and meant for debugging: the mesh data structures and the
+//! other maps keep using plain handles.
+//!
+//! ```
+//! use lox::{FaceHandle, Handle, map::GenDenseMap};
+//!
+//! let mut map = GenDenseMap::new();
+//! let old = map.insert(FaceHandle::new(3), "old");
+//! assert_eq!(map.remove(old), Some("old"));
+//!
+//! // The slot is reused, but the old handle does not see the new value.
+//! let new = map.insert(FaceHandle::new(3), "new");
+//! assert_eq!(map.get(old), None);
+//! assert_eq!(map.get(new), Some(&"new"));
+//! ```
+
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    hsize,
+    prelude::*,
+};
+use super::{DenseMap, PropStore, PropStoreMut};
+
+
+/// A handle together with the generation of its slot at the time the handle
+/// was created. Obtained from [`GenDenseMap`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GenHandle<H: Handle> {
+    handle: H,
+    generation: u32,
+}
+
+impl<H: Handle> GenHandle<H> {
+    /// Returns the plain handle, without the generation.
+    pub fn handle(&self) -> H {
+        self.handle
+    }
+
+    /// Returns the generation of this handle.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+impl<H: Handle> fmt::Debug for GenHandle<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}@{}", self.handle, self.generation)
+    }
+}
+
+
+/// A map like [`DenseMap`], but accessed via [`GenHandle`]s so that handles
+/// to removed values are detected.
+///
+/// Each slot has a generation counter that is incremented every time the
+/// value in it is removed. All accessors return `None` (or panic, for
+/// indexing) if the generation of the given handle does not match the
+/// current generation of the slot. The counter wraps around after `2^32`
+/// removals from the same slot, so in theory, a very old handle could become
+/// valid again.
+///
+/// Memory-wise, this behaves like a [`DenseMap`] with an additional `u32`
+/// per slot.
+#[derive(Clone)]
+pub struct GenDenseMap<H: Handle, T> {
+    values: DenseMap<H, T>,
+
+    /// The current generation of each slot that was ever used. Not removed
+    /// when the value is removed.
+    generations: DenseMap<H, u32>,
+}
+
+impl<H: Handle, T> GenDenseMap<H, T> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self {
+            values: DenseMap::new(),
+            generations: DenseMap::new(),
+        }
+    }
+
+    /// Returns the number of values in this map.
+    pub fn num_elements(&self) -> hsize {
+        self.values.num_props()
+    }
+
+    /// Returns `true` if there are no values in this map.
+    pub fn is_empty(&self) -> bool {
+        self.num_elements() == 0
+    }
+
+    /// Inserts `value` for `handle` and returns the generational handle to
+    /// access it.
+    ///
+    /// If there already is a value for `handle`, it is replaced and the
+    /// generation stays the same, i.e. existing handles to this slot see the
+    /// new value. If you want old handles to become stale, [`remove`] the old
+    /// value first.
+    ///
+    /// [`remove`]: GenDenseMap::remove
+    pub fn insert(&mut self, handle: H, value: T) -> GenHandle<H> {
+        let generation = *self.generations.get_or_insert(handle, 0);
+        self.values.insert(handle, value);
+        GenHandle { handle, generation }
+    }
+
+    /// Removes the value referred to by `handle` and returns it. Afterwards,
+    /// all handles to this slot are stale. Returns `None` (and does nothing)
+    /// if `handle` is already stale or there is no value.
+    pub fn remove(&mut self, handle: GenHandle<H>) -> Option<T> {
+        if !self.contains(handle) {
+            return None;
+        }
+
+        let generation = &mut self.generations[handle.handle];
+        *generation = generation.wrapping_add(1);
+        self.values.remove(handle.handle)
+    }
+
+    /// Returns `true` if `handle` is not stale and refers to a value.
+    pub fn contains(&self, handle: GenHandle<H>) -> bool {
+        self.current(handle.handle) == Some(handle)
+    }
+
+    /// Returns a reference to the value referred to by `handle`, or `None` if
+    /// the handle is stale or there is no value.
+    pub fn get(&self, handle: GenHandle<H>) -> Option<&T> {
+        if self.contains(handle) {
+            self.values.get_ref(handle.handle)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the value referred to by `handle`, or
+    /// `None` if the handle is stale or there is no value.
+    pub fn get_mut(&mut self, handle: GenHandle<H>) -> Option<&mut T> {
+        if self.contains(handle) {
+            self.values.get_mut(handle.handle)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the up-to-date generational handle for the value stored for
+    /// the plain `handle`, or `None` if there is no such value.
+    pub fn current(&self, handle: H) -> Option<GenHandle<H>> {
+        if !self.values.contains_handle(handle) {
+            return None;
+        }
+
+        Some(GenHandle { handle, generation: self.generations[handle] })
+    }
+
+    /// Returns an iterator over all up-to-date handles and their values, in
+    /// ascending handle order.
+    pub fn iter(&self) -> impl Iterator<Item = (GenHandle<H>, &T)> + '_ {
+        self.values.iter().map(move |(handle, value)| {
+            (GenHandle { handle, generation: self.generations[handle] }, value)
+        })
+    }
+}
+
+impl<H: Handle, T> Default for GenDenseMap<H, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: Handle, T> Empty for GenDenseMap<H, T> {
+    fn empty() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: Handle, T> Index<GenHandle<H>> for GenDenseMap<H, T> {
+    type Output = T;
+    fn index(&self, handle: GenHandle<H>) -> &Self::Output {
+        match self.get(handle) {
+            None => panic!("stale or missing handle '{:?}'", handle),
+            Some(r) => r,
+        }
+    }
+}
+
+impl<H: Handle, T> IndexMut<GenHandle<H>> for GenDenseMap<H, T> {
+    fn index_mut(&mut self, handle: GenHandle<H>) -> &mut Self::Output {
+        match self.get_mut(handle) {
+            None => panic!("stale or missing handle '{:?}'", handle),
+            Some(r) => r,
+        }
+    }
+}
+
+impl<H: Handle, T: fmt::Debug> fmt::Debug for GenDenseMap<H, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_handles() {
+        let mut map = GenDenseMap::new();
+        let a = map.insert(VertexHandle::new(0), 'a');
+        let b = map.insert(VertexHandle::new(1), 'b');
+        assert_eq!(map.num_elements(), 2);
+        assert_eq!(a.generation(), 0);
+
+        assert_eq!(map.remove(a), Some('a'));
+        assert_eq!(map.remove(a), None);
+        assert!(!map.contains(a));
+        assert_eq!(map.current(VertexHandle::new(0)), None);
+
+        let c = map.insert(VertexHandle::new(0), 'c');
+        assert_eq!(c.handle(), a.handle());
+        assert_eq!(c.generation(), 1);
+        assert_eq!(map.get(a), None);
+        assert_eq!(map.get_mut(a), None);
+        assert_eq!(map[c], 'c');
+        assert_eq!(map[b], 'b');
+        assert_eq!(map.current(VertexHandle::new(0)), Some(c));
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(c, &'c'), (b, &'b')]);
+    }
+
+    #[test]
+    fn replacing_keeps_generation() {
+        let mut map = GenDenseMap::new();
+        let a = map.insert(FaceHandle::new(4), 1);
+        let b = map.insert(FaceHandle::new(4), 2);
+        assert_eq!(a, b);
+        assert_eq!(map[a], 2);
+
+        map[a] += 1;
+        assert_eq!(map.get(b), Some(&3));
+        assert_eq!(map.num_elements(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "stale or missing handle 'F4@0'")]
+    fn index_stale() {
+        let mut map = GenDenseMap::new();
+        let a = map.insert(FaceHandle::new(4), 1);
+        map.remove(a);
+        map[a];
+    }
+}
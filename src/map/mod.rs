@@ -29,7 +29,7 @@
 //!
 //! # Implementations
 //!
-//! There are currently two implementations of `PropStore`, allowing you to
+//! There are currently two implementations of `PropStoreMut`, allowing you to
 //! store properties:
 //!
 //! - [`SparseMap`]: A `HashMap` under the hood. Performs well in almost all
@@ -39,13 +39,21 @@
 //!   in these cases. Pretty bad in all other cases. Uses the handle's index
 //!   to index into a `Vec`.
 //!
+//! [`EmptyMap`] implements `PropStore`, but not `PropStoreMut`: it never
+//! stores anything and returns `None` for all handles.
+//!
 //! In addition to the types above, the following types also (but only)
 //! implement `PropMap`.
 //!
+//! - [`DenseBitSet`]: Stores a single bit per handle, ideal for `bool` flags.
+//!   Built on top of [`DenseSet`][set::DenseSet].
 //! - [`ConstMap`]: Returns the same prop value for all handles.
-//! - [`EmptyMap`]: Returns `None` for all handles.
 //! - [`FnMap`]: Uses a closure to calculate the prop for a handle.
 //!
+//! Finally, [`GenDenseMap`] is a debugging aid that is accessed via
+//! generational handles ([`GenHandle`]) instead of plain handles, so that
+//! using a handle after its value was removed is detected.
+//!
 //!
 //!
 //!
@@ -63,15 +71,19 @@ use crate::{
 mod tests;
 
 pub mod adaptors;
+mod bit_set;
 mod dense;
 mod fn_map;
+mod generational;
 pub mod set;
 mod sparse;
 mod special_maps;
 mod util;
 
 pub use self::{
+    bit_set::{DenseBitSet, SetHandles},
     fn_map::FnMap,
+    generational::{GenDenseMap, GenHandle},
     sparse::SparseMap,
     special_maps::{ConstMap, EmptyMap},
     dense::DenseMap,
@@ -219,7 +231,90 @@ pub trait PropMap<H: Handle> {
         }
     }
 
-    // TODO: filter
+    /// Creates a new prop map that combines this map with `other`. The new map
+    /// returns a tuple of both properties, but only for handles for which both
+    /// maps have a property. Very similar to [`Iterator::zip`].
+    ///
+    /// The properties of both maps are cloned into the returned tuple, which
+    /// is cheap for the usual `Copy` props like positions and normals.
+    ///
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lox::{
+    ///     VertexHandle,
+    ///     prelude::*,
+    ///     map::{DenseMap, SparseMap},
+    /// };
+    ///
+    /// let v0 = VertexHandle::from_usize(0);
+    /// let v1 = VertexHandle::from_usize(1);
+    ///
+    /// let mut positions = DenseMap::new();
+    /// positions.insert(v0, [0.0, 1.0, 2.0]);
+    /// positions.insert(v1, [3.0, 4.0, 5.0]);
+    ///
+    /// let mut weights = SparseMap::new();
+    /// weights.insert(v1, 0.5);
+    ///
+    /// let zipped = positions.zip(&weights);
+    /// assert_eq!(zipped.get(v0), None);
+    /// assert_eq!(zipped.get(v1).map(|v| *v), Some(([3.0, 4.0, 5.0], 0.5)));
+    /// ```
+    fn zip<'m, O>(&'m self, other: &'m O) -> adaptors::Zip<'m, Self, O>
+    where
+        Self: Sized,
+        O: PropMap<H>,
+    {
+        adaptors::Zip {
+            first: self,
+            second: other,
+        }
+    }
+
+    /// Creates a new prop map that only contains the properties of this map
+    /// whose handle satisfies the given predicate. Very similar to
+    /// [`Iterator::filter`].
+    ///
+    /// Note that this changes [`contains_handle`][Self::contains_handle]: for
+    /// handles rejected by `predicate`, the new map returns `None` from `get`
+    /// even if this map has a property for it. In particular, if this map is
+    /// complete (returns `Some` for all handles of a mesh), the filtered map
+    /// generally is not.
+    ///
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lox::{
+    ///     VertexHandle,
+    ///     prelude::*,
+    ///     map::DenseMap,
+    /// };
+    ///
+    /// let v0 = VertexHandle::from_usize(0);
+    /// let v1 = VertexHandle::from_usize(1);
+    ///
+    /// let mut positions = DenseMap::new();
+    /// positions.insert(v0, [0.0, 1.0, 2.0]);
+    /// positions.insert(v1, [3.0, 4.0, 5.0]);
+    ///
+    /// let selected = positions.filter(|vh| vh == v1);
+    /// assert_eq!(selected.get(v0), None);
+    /// assert_eq!(selected.get(v1).map(|v| *v), Some([3.0, 4.0, 5.0]));
+    /// assert!(!selected.contains_handle(v0));
+    /// ```
+    fn filter<F>(&self, predicate: F) -> adaptors::Filter<'_, Self, F>
+    where
+        Self: Sized,
+        F: Fn(H) -> bool,
+    {
+        adaptors::Filter {
+            inner: self,
+            predicate,
+        }
+    }
 }
 
 
@@ -242,6 +337,13 @@ pub trait PropStore<H: Handle>:
     /// Returns the number of properties stored in this map.
     fn num_props(&self) -> hsize;
 
+    /// Returns the number of properties as `usize`, for pre-sizing buffers
+    /// that will hold all of them (e.g. when serializing). The default
+    /// implementation simply returns [`num_props`][Self::num_props].
+    fn size_hint(&self) -> usize {
+        self.num_props() as usize
+    }
+
     /// Type returned by [`iter`][Self::iter].
     type Iter<'s>: Iterator<Item = (H, &'s Self::Output)> where Self: 's;
 
@@ -282,6 +384,32 @@ pub trait PropStoreMut<H: Handle>: Empty + PropStore<H> + ops::IndexMut<H> {
     where
         Self::Output: Sized;
 
+    /// Returns a mutable reference to the property associated with `handle`.
+    /// If there is no such property, `default` is inserted first.
+    fn get_or_insert(&mut self, handle: H, default: Self::Output) -> &mut Self::Output
+    where
+        Self::Output: Sized,
+    {
+        self.get_or_insert_with(handle, || default)
+    }
+
+    /// Returns a mutable reference to the property associated with `handle`.
+    /// If there is no such property, the result of `default` is inserted
+    /// first. `default` is only called in that case.
+    fn get_or_insert_with(
+        &mut self,
+        handle: H,
+        default: impl FnOnce() -> Self::Output,
+    ) -> &mut Self::Output
+    where
+        Self::Output: Sized,
+    {
+        if !self.contains_handle(handle) {
+            self.insert(handle, default());
+        }
+        &mut self[handle]
+    }
+
     /// Removes the property associated with `handle` and returns it. If no
     /// property was associated with `handle`, nothing is removed and `None` is
     /// returned.
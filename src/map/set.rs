@@ -18,6 +18,8 @@ use super::{
 /// A handle set that uses a bit vector to store handles.
 ///
 /// See [`DenseMap`] for more information on memory requirements and speed.
+/// [`DenseBitSet`][super::DenseBitSet] wraps this type to offer it as a map
+/// from handles to `bool` flags.
 pub type DenseSet<H> = Set<H, DenseMap<H, ()>>;
 
 /// A handle set that uses hash set store handles.
@@ -115,6 +115,20 @@ impl<H: Handle + Hash, T> PropStoreMut<H> for SparseMap<H, T> {
     }
 }
 
+impl<H: Handle + Hash, T> Extend<(H, T)> for SparseMap<H, T> {
+    fn extend<I: IntoIterator<Item = (H, T)>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl<H: Handle + Hash, T> FromIterator<(H, T)> for SparseMap<H, T> {
+    fn from_iter<I: IntoIterator<Item = (H, T)>>(iter: I) -> Self {
+        let mut out = Self::new();
+        out.extend(iter);
+        out
+    }
+}
+
 
 gen_mapped_iter!(
     Iter<'a, H, T>(std::collections::hash_map::Iter<'a, H, T>);
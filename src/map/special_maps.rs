@@ -1,11 +1,15 @@
-use std::marker::PhantomData;
+use std::{iter, marker::PhantomData, ops::Index};
 
-use crate::Handle;
-use super::{PropMap, Value};
+use crate::{Handle, hsize};
+use super::{PropMap, PropStore, Value};
 
 
 /// A map that always returns the same value for all handles (always `Some()`).
 ///
+/// This map does not implement [`PropStore`], as it has a value for *every*
+/// possible handle: there is no finite set of handles to iterate over and
+/// `num_props` could not return a meaningful number.
+///
 /// # Example
 ///
 /// ```
@@ -34,12 +38,16 @@ impl<T, H: Handle> PropMap<H> for ConstMap<T> {
 /// A map that always returns `None`. It is also generic over the type it
 /// returns, so as far as the type system is concerned, it can return any type.
 ///
+/// This map also implements [`PropStore`] (with no props and empty iterators),
+/// so it can be used as a placeholder for functions requiring a `PropStore`.
+/// Indexing it always panics.
+///
 /// # Example
 ///
 /// ```
 /// use lox::{
 ///     VertexHandle,
-///     map::{EmptyMap, PropMap},
+///     map::{EmptyMap, PropMap, PropStore},
 /// };
 ///
 /// // When the type is already fixed somewhere, you don't need to specify it
@@ -49,6 +57,12 @@ impl<T, H: Handle> PropMap<H> for ConstMap<T> {
 /// // If the type is not fixed, you might need to manually specify it like so
 /// fn bar(map: &impl PropMap<VertexHandle>) {}
 /// bar(&EmptyMap::<u32>::new());
+///
+/// // It can also be used as a `PropStore`
+/// fn baz(map: &impl PropStore<VertexHandle>) -> usize {
+///     map.iter().count()
+/// }
+/// assert_eq!(baz(&EmptyMap::<u32>::new()), 0);
 /// ```
 #[derive(Clone, Copy, Debug)]
 pub struct EmptyMap<T>(PhantomData<T>);
@@ -67,3 +81,25 @@ impl<T, H: Handle> PropMap<H> for EmptyMap<T> {
         None
     }
 }
+
+impl<T, H: Handle> Index<H> for EmptyMap<T> {
+    type Output = T;
+    fn index(&self, handle: H) -> &Self::Output {
+        panic!("no property found for handle '{:?}' (indexed `EmptyMap`)", handle)
+    }
+}
+
+impl<T, H: Handle> PropStore<H> for EmptyMap<T> {
+    fn get_ref(&self, _: H) -> Option<&Self::Output> {
+        None
+    }
+
+    fn num_props(&self) -> hsize {
+        0
+    }
+
+    type Iter<'s> = iter::Empty<(H, &'s T)> where Self: 's;
+    fn iter(&self) -> Self::Iter<'_> {
+        iter::empty()
+    }
+}
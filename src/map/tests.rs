@@ -16,6 +16,7 @@ where
     T: Eq + fmt::Debug + Hash,
 {
     assert_eq!(left.num_props(), right.len() as hsize);
+    assert_eq!(left.size_hint(), right.len());
     assert_eq!(left.is_empty(), right.is_empty());
 
     macro_rules! assert_iter_eq {
@@ -205,5 +206,62 @@ macro_rules! gen_tests_for_store_impl {
 
             check!(m, [(h(0), "a"), (h(1), "nonono"), (h(2), "c"), (h(3), "d"), (h(4), "yes")]);
         }
+
+        #[test]
+        fn collect() {
+            let mut m = [(h(3), "a"), (h(0), "b"), (h(7), "c"), (h(3), "d")]
+                .into_iter()
+                .collect::<$name<_, _>>();
+            check!(m, [(h(0), "b"), (h(3), "d"), (h(7), "c")]);
+        }
+
+        #[test]
+        fn extend() {
+            let mut m = $name::empty();
+            m.insert(h(1), "a");
+            m.insert(h(2), "b");
+
+            m.extend([(h(2), "x"), (h(5), "c")]);
+            check!(m, [(h(1), "a"), (h(2), "x"), (h(5), "c")]);
+        }
+
+        #[test]
+        #[should_panic(expected = "V7")]
+        fn index_missing_names_handle() {
+            let mut m = $name::empty();
+            m.insert(crate::VertexHandle::from_usize(3), "a");
+            let _ = m[crate::VertexHandle::from_usize(7)];
+        }
+
+        #[test]
+        #[should_panic(expected = "V7")]
+        fn index_mut_missing_names_handle() {
+            let mut m = $name::empty();
+            m.insert(crate::VertexHandle::from_usize(3), "a");
+            m[crate::VertexHandle::from_usize(7)] = "b";
+        }
+
+        #[test]
+        fn get_or_insert() {
+            fn count<S>(words: &[usize]) -> S
+            where
+                S: PropMap<FaceHandle, Target = u32> + PropStoreMut<FaceHandle, Output = u32>,
+            {
+                let mut counts = S::empty();
+                for &w in words {
+                    *counts.get_or_insert(h(w), 0) += 1;
+                }
+                counts
+            }
+
+            let mut m: $name<_, u32> = count(&[3, 1, 3, 0, 3]);
+            check!(m, [(h(0), 1u32), (h(1), 1), (h(3), 3)]);
+
+            let mut called = false;
+            *m.get_or_insert_with(h(1), || { called = true; 0 }) += 10;
+            assert!(!called);
+            *m.get_or_insert_with(h(2), || 20) += 1;
+            check!(m, [(h(0), 1u32), (h(1), 11), (h(2), 21), (h(3), 3)]);
+        }
     }
 }
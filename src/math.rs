@@ -0,0 +1,63 @@
+//! Small math helpers shared by different algorithms.
+
+use lina::{Vec3, cross, dot};
+
+use crate::util::PrimitiveFloat;
+
+
+/// Converts an angle from degrees to radians.
+pub fn deg_to_rad<S: PrimitiveFloat>(deg: S) -> S {
+    deg * S::PI() / S::from_f32(180.0)
+}
+
+/// Converts an angle from radians to degrees.
+pub fn rad_to_deg<S: PrimitiveFloat>(rad: S) -> S {
+    rad * S::from_f32(180.0) / S::PI()
+}
+
+/// Returns the angle between `a` and `b` in radians, in the range `[0, π]`.
+///
+/// Instead of `acos` of the normalized dot product (which needs clamping, as
+/// rounding errors can push the argument out of `[-1, 1]` and result in NaN),
+/// this uses `atan2` of the cross and dot products. That is well defined for
+/// all inputs and also more precise for nearly (anti-)parallel vectors. If one
+/// of the vectors is zero, 0 is returned.
+pub fn angle_between<S: PrimitiveFloat>(a: Vec3<S>, b: Vec3<S>) -> S {
+    cross(a, b).length().atan2(dot(a, b))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(deg_to_rad(180.0f64), std::f64::consts::PI);
+        assert_eq!(rad_to_deg(std::f64::consts::FRAC_PI_2), 90.0);
+        assert!((rad_to_deg(deg_to_rad(37.5f64)) - 37.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn orthogonal() {
+        let angle = angle_between(Vec3::new(2.0f64, 0.0, 0.0), Vec3::new(0.0, 0.0, 3.0));
+        assert_eq!(rad_to_deg(angle), 90.0);
+    }
+
+    #[test]
+    fn nearly_parallel() {
+        let a = Vec3::new(1.0f64, 1.0, 1.0);
+        let b = Vec3::new(1.0, 1.0, 1.0 + 1e-15);
+        let angle = angle_between(a, b);
+        assert!(!angle.is_nan());
+        assert!(angle < 1e-12);
+
+        let angle = angle_between(a, -a);
+        assert_eq!(angle, std::f64::consts::PI);
+    }
+
+    #[test]
+    fn zero_vector() {
+        assert_eq!(angle_between(Vec3::zero(), Vec3::new(1.0f64, 0.0, 0.0)), 0.0);
+    }
+}
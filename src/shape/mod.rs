@@ -0,0 +1,881 @@
+//! Procedurally generated meshes of simple geometric shapes.
+//!
+//! Each shape is a plain struct with public fields (and a `Default` impl)
+//! describing its parameters. The [`Shape`] trait builds the shape into any
+//! mesh implementing [`MeshMut`] while writing the vertex positions into a
+//! property map. All positions are calculated in `f64` and then cast to the
+//! scalar type of the map.
+//!
+//! By default, only positions are generated. Per-vertex normals and texture
+//! coordinates can be requested via [`Shape::with_normals`] and
+//! [`Shape::with_texcoords`]:
+//!
+//! ```
+//! use lox::{
+//!     core::half_edge::{HalfEdgeMesh, TriConfig},
+//!     prelude::*,
+//!     shape::{Shape, Sphere},
+//! };
+//!
+//! let (mesh, positions, attrs) = Sphere::default()
+//!     .with_normals()
+//!     .with_texcoords()
+//!     .build::<HalfEdgeMesh<TriConfig>>();
+//!
+//! let normals = attrs.normals.unwrap();
+//! assert_eq!(normals.num_props(), mesh.num_vertices());
+//! assert_eq!(positions.num_props(), mesh.num_vertices());
+//! assert!(attrs.texcoords.is_some());
+//! ```
+
+use std::f64::consts;
+
+use lina::{Point2, Point3, Vec3};
+
+use crate::{
+    cast, hsize,
+    prelude::*,
+    map::DenseMap,
+    util::Pos3Like,
+};
+
+
+/// Optional per-vertex attributes generated alongside the positions of a
+/// shape. Only the attributes that are `Some` are filled.
+#[derive(Debug, Clone, Default)]
+pub struct VertexAttributes {
+    /// Unit length normals pointing out of the shape.
+    pub normals: Option<DenseMap<VertexHandle, Vec3<f64>>>,
+
+    /// Texture coordinates in the range `[0, 1]`. See the specific shape for
+    /// how they are laid out.
+    pub texcoords: Option<DenseMap<VertexHandle, Point2<f64>>>,
+}
+
+/// A shape that can be built into a mesh.
+pub trait Shape {
+    /// Adds the shape's vertices and faces to `mesh`, inserts the position of
+    /// each new vertex into `vertex_positions` and additionally inserts
+    /// normals and texture coordinates into the maps of `attributes` that
+    /// are `Some`.
+    ///
+    /// The mesh does not need to be empty: elements already in the mesh are
+    /// not touched. Panics if the shape's parameters are invalid (see the
+    /// documentation of the specific shape).
+    fn build_with_attributes_to<MeshT, MapT>(
+        &self,
+        mesh: &mut MeshT,
+        vertex_positions: &mut MapT,
+        attributes: &mut VertexAttributes,
+    )
+    where
+        MeshT: MeshMut,
+        MapT: PropStoreMut<VertexHandle>,
+        MapT::Target: Pos3Like;
+
+    /// Adds the shape's vertices and faces to `mesh` and inserts the position
+    /// of each new vertex into `vertex_positions`.
+    ///
+    /// The mesh does not need to be empty: elements already in the mesh are
+    /// not touched. Panics if the shape's parameters are invalid (see the
+    /// documentation of the specific shape).
+    fn build_to<MeshT, MapT>(&self, mesh: &mut MeshT, vertex_positions: &mut MapT)
+    where
+        MeshT: MeshMut,
+        MapT: PropStoreMut<VertexHandle>,
+        MapT::Target: Pos3Like,
+    {
+        self.build_with_attributes_to(mesh, vertex_positions, &mut VertexAttributes::default());
+    }
+
+    /// Builds the shape into a new mesh and returns it together with the
+    /// vertex positions.
+    fn build<MeshT: MeshMut>(&self) -> (MeshT, DenseMap<VertexHandle, Point3<f64>>) {
+        let mut mesh = MeshT::empty();
+        let mut positions = DenseMap::new();
+        self.build_to(&mut mesh, &mut positions);
+        (mesh, positions)
+    }
+
+    /// Returns a builder that also generates vertex normals.
+    fn with_normals(self) -> WithAttributes<Self>
+    where
+        Self: Sized,
+    {
+        WithAttributes { shape: self, normals: false, texcoords: false }.with_normals()
+    }
+
+    /// Returns a builder that also generates vertex texture coordinates.
+    fn with_texcoords(self) -> WithAttributes<Self>
+    where
+        Self: Sized,
+    {
+        WithAttributes { shape: self, normals: false, texcoords: false }.with_texcoords()
+    }
+}
+
+/// A shape together with the set of vertex attributes to generate. Created
+/// by [`Shape::with_normals`] and [`Shape::with_texcoords`].
+#[derive(Debug, Clone, Copy)]
+pub struct WithAttributes<S> {
+    shape: S,
+    normals: bool,
+    texcoords: bool,
+}
+
+impl<S: Shape> WithAttributes<S> {
+    /// Enables generation of vertex normals.
+    pub fn with_normals(self) -> Self {
+        Self { normals: true, ..self }
+    }
+
+    /// Enables generation of vertex texture coordinates.
+    pub fn with_texcoords(self) -> Self {
+        Self { texcoords: true, ..self }
+    }
+
+    /// Like [`Shape::build_to`], but also returns the enabled attributes.
+    pub fn build_to<MeshT, MapT>(
+        &self,
+        mesh: &mut MeshT,
+        vertex_positions: &mut MapT,
+    ) -> VertexAttributes
+    where
+        MeshT: MeshMut,
+        MapT: PropStoreMut<VertexHandle>,
+        MapT::Target: Pos3Like,
+    {
+        let mut attributes = VertexAttributes {
+            normals: self.normals.then(DenseMap::new),
+            texcoords: self.texcoords.then(DenseMap::new),
+        };
+        self.shape.build_with_attributes_to(mesh, vertex_positions, &mut attributes);
+        attributes
+    }
+
+    /// Like [`Shape::build`], but also returns the enabled attributes.
+    pub fn build<MeshT: MeshMut>(
+        &self,
+    ) -> (MeshT, DenseMap<VertexHandle, Point3<f64>>, VertexAttributes) {
+        let mut mesh = MeshT::empty();
+        let mut positions = DenseMap::new();
+        let attributes = self.build_to(&mut mesh, &mut positions);
+        (mesh, positions, attributes)
+    }
+}
+
+/// Destination of the generated vertex data.
+struct Out<'a, MapT> {
+    positions: &'a mut MapT,
+    attributes: &'a mut VertexAttributes,
+}
+
+impl<MapT> Out<'_, MapT>
+where
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like,
+{
+    fn reserve(&mut self, count: hsize) {
+        self.positions.reserve(count);
+        if let Some(normals) = &mut self.attributes.normals {
+            normals.reserve(count);
+        }
+        if let Some(texcoords) = &mut self.attributes.texcoords {
+            texcoords.reserve(count);
+        }
+    }
+
+    /// Adds a vertex to the mesh and stores its position and the enabled
+    /// attributes. `normal` does not need to be normalized.
+    fn add_vertex<MeshT: MeshMut>(
+        &mut self,
+        mesh: &mut MeshT,
+        pos: Point3<f64>,
+        normal: Vec3<f64>,
+        texcoord: Point2<f64>,
+    ) -> VertexHandle {
+        let v = mesh.add_vertex();
+        self.positions.insert(v, pos.map_scalar(cast::lossy));
+        if let Some(normals) = &mut self.attributes.normals {
+            normals.insert(v, normal.normalized());
+        }
+        if let Some(texcoords) = &mut self.attributes.texcoords {
+            texcoords.insert(v, texcoord);
+        }
+        v
+    }
+}
+
+
+/// A UV sphere whose poles point towards +z and -z.
+///
+/// Vertex normals point away from the center. The texture coordinates are an
+/// equirectangular mapping: `u` grows from 0 to 1 with the longitude
+/// (starting in +x direction), `v` goes from 0 at the south pole to 1 at the
+/// north pole. Since vertices are shared across the seam, the faces of the
+/// last longitude segment wrap around from `u` close to 1 back to 0.
+#[derive(Debug, Clone, Copy)]
+pub struct Sphere {
+    /// The center of the sphere. *Default*: `[0, 0, 0]`.
+    pub center: Point3<f64>,
+
+    /// The radius of the sphere (the distance from the vertices to the
+    /// center). *Default*: 1.0.
+    pub radius: f64,
+
+    /// Number of latitude lines (excluding poles). *Default*: 16.
+    ///
+    /// These are the lines parallel to the equator. A value of 1 means that
+    /// there is just the equator which is connected directly to both poles.
+    ///
+    /// Value must not be 0 or else building the sphere will panic.
+    pub num_latitudes: hsize,
+
+    /// Number of longitude lines. *Default*: 24.
+    ///
+    /// These are the lines going perpendicular to the equator and going
+    /// through both poles. A value of `n` means that each latitude line (for
+    /// example: the equator) contains `n` vertices and `n` edges.
+    ///
+    /// Value must be greater than or equal to 3 or else building the sphere
+    /// will panic.
+    pub num_longitudes: hsize,
+}
+
+impl Default for Sphere {
+    fn default() -> Self {
+        Self {
+            center: Point3::origin(),
+            radius: 1.0,
+            num_latitudes: 16,
+            num_longitudes: 24,
+        }
+    }
+}
+
+impl Shape for Sphere {
+    fn build_with_attributes_to<MeshT, MapT>(
+        &self,
+        mesh: &mut MeshT,
+        vertex_positions: &mut MapT,
+        attributes: &mut VertexAttributes,
+    )
+    where
+        MeshT: MeshMut,
+        MapT: PropStoreMut<VertexHandle>,
+        MapT::Target: Pos3Like,
+    {
+        assert!(
+            self.num_latitudes >= 1,
+            "trying to build a sphere with {} latitudes (minimum is 1)",
+            self.num_latitudes,
+        );
+        assert!(
+            self.num_longitudes >= 3,
+            "trying to build a sphere with {} longitudes (minimum is 3)",
+            self.num_longitudes,
+        );
+
+        // - Vertices: we have two poles plus a vertex whenever a latitude and
+        //   longitude line meet.
+        // - Faces: on each pole (times 2) we have one triangle for each
+        //   longitude line. Adding to that is a quad face (two triangles) for
+        //   each longitude line between two adjacent latitude lines (that's
+        //   the minus 1).
+        let vertex_count = 2 + self.num_latitudes * self.num_longitudes;
+        let face_count = 2 * self.num_longitudes
+            + 2 * ((self.num_latitudes - 1) * self.num_longitudes);
+        mesh.reserve_for_vertices(vertex_count);
+        mesh.reserve_for_faces(face_count);
+        let mut out = Out { positions: vertex_positions, attributes };
+        out.reserve(vertex_count);
+
+        let num_longs = self.num_longitudes as usize;
+        let mut add = |mesh: &mut MeshT, normal: Vec3<f64>, texcoord: Point2<f64>| {
+            out.add_vertex(mesh, self.center + normal * self.radius, normal, texcoord)
+        };
+
+        // Add north pole and all latitude lines, each connected to the
+        // previous one (or the north pole).
+        let north_pole = add(mesh, Vec3::unit_z(), Point2::new(0.5, 1.0));
+        let mut last_latitude_points: Vec<VertexHandle> = Vec::new();
+        for lat in 0..self.num_latitudes {
+            let theta = consts::PI * ((lat + 1) as f64) / ((self.num_latitudes + 1) as f64);
+
+            let new_latitude_points = (0..self.num_longitudes).map(|long| {
+                let phi = 2.0 * consts::PI * (long as f64) / (self.num_longitudes as f64);
+                let normal = Vec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                );
+                let texcoord = Point2::new(
+                    (long as f64) / (self.num_longitudes as f64),
+                    1.0 - theta / consts::PI,
+                );
+                add(mesh, normal, texcoord)
+            }).collect::<Vec<_>>();
+
+            for i in 0..num_longs {
+                let new_0 = new_latitude_points[i];
+                let new_1 = new_latitude_points[(i + 1) % num_longs];
+                if lat == 0 {
+                    mesh.add_triangle([north_pole, new_0, new_1]);
+                } else {
+                    let last_0 = last_latitude_points[i];
+                    let last_1 = last_latitude_points[(i + 1) % num_longs];
+                    mesh.add_triangle([last_0, new_1, last_1]);
+                    mesh.add_triangle([last_0, new_0, new_1]);
+                }
+            }
+
+            last_latitude_points = new_latitude_points;
+        }
+
+        // Add south pole and adjacent faces
+        let south_pole = add(mesh, -Vec3::<f64>::unit_z(), Point2::new(0.5, 0.0));
+        for i in 0..num_longs {
+            mesh.add_triangle([
+                south_pole,
+                last_latitude_points[(i + 1) % num_longs],
+                last_latitude_points[i],
+            ]);
+        }
+    }
+}
+
+
+/// Adds `segments` vertices on a circle parallel to the XY-plane around
+/// `center`, in CCW order when seen from +z, starting in +x direction.
+///
+/// `normal` maps the outwards pointing unit direction from the center to the
+/// vertex normal. The texture coordinates are `[i / segments, v]` for the
+/// `i`-th vertex.
+fn add_ring<MeshT, MapT>(
+    mesh: &mut MeshT,
+    out: &mut Out<'_, MapT>,
+    center: Point3<f64>,
+    radius: f64,
+    segments: hsize,
+    v: f64,
+    normal: impl Fn(Vec3<f64>) -> Vec3<f64>,
+) -> Vec<VertexHandle>
+where
+    MeshT: MeshMut,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like,
+{
+    (0..segments).map(|i| {
+        let phi = 2.0 * consts::PI * (i as f64) / (segments as f64);
+        let dir = Vec3::new(phi.cos(), phi.sin(), 0.0);
+        let texcoord = Point2::new((i as f64) / (segments as f64), v);
+        out.add_vertex(mesh, center + dir * radius, normal(dir), texcoord)
+    }).collect()
+}
+
+/// Closes the given ring with a fan of triangles around a new vertex at
+/// `center`. The faces point towards +z if `upwards` is `true`, and towards
+/// -z otherwise. The center vertex gets the texture coordinate `[0.5, v]`.
+fn add_cap<MeshT, MapT>(
+    mesh: &mut MeshT,
+    out: &mut Out<'_, MapT>,
+    ring: &[VertexHandle],
+    center: Point3<f64>,
+    upwards: bool,
+    v: f64,
+)
+where
+    MeshT: MeshMut,
+    MapT: PropStoreMut<VertexHandle>,
+    MapT::Target: Pos3Like,
+{
+    let normal = if upwards { Vec3::unit_z() } else { -Vec3::<f64>::unit_z() };
+    let c = out.add_vertex(mesh, center, normal, Point2::new(0.5, v));
+    for i in 0..ring.len() {
+        let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+        if upwards {
+            mesh.add_triangle([c, a, b]);
+        } else {
+            mesh.add_triangle([c, b, a]);
+        }
+    }
+}
+
+
+/// A closed cylinder whose axis is parallel to the z-axis, with flat caps at
+/// the top and bottom.
+///
+/// Each cap is a fan of triangles around a center vertex, the side is made of
+/// one quad (two triangles) per segment.
+///
+/// The outline vertices are shared by the side and a cap, so their normal is
+/// the average of the side and cap normal (tilted by 45°). The cap centers'
+/// normals point along the axis. Texture coordinates wrap around the side:
+/// `u` grows with the angle (starting in +x direction), `v` is 0 at the
+/// bottom and 1 at the top.
+#[derive(Debug, Clone, Copy)]
+pub struct Cylinder {
+    /// The center of the cylinder (halfway between the cap centers).
+    /// *Default*: `[0, 0, 0]`.
+    pub center: Point3<f64>,
+
+    /// The radius of the caps (the distance from the outer vertices to the
+    /// axis). *Default*: 1.0.
+    pub radius: f64,
+
+    /// The distance between the two caps. *Default*: 2.0.
+    pub height: f64,
+
+    /// The number of vertices on each cap's outline. Value must be greater
+    /// than or equal to 3 or else building the cylinder will panic.
+    /// *Default*: 24.
+    pub segments: hsize,
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Self {
+            center: Point3::origin(),
+            radius: 1.0,
+            height: 2.0,
+            segments: 24,
+        }
+    }
+}
+
+impl Shape for Cylinder {
+    fn build_with_attributes_to<MeshT, MapT>(
+        &self,
+        mesh: &mut MeshT,
+        vertex_positions: &mut MapT,
+        attributes: &mut VertexAttributes,
+    )
+    where
+        MeshT: MeshMut,
+        MapT: PropStoreMut<VertexHandle>,
+        MapT::Target: Pos3Like,
+    {
+        assert!(
+            self.segments >= 3,
+            "trying to build a cylinder with {} segments (minimum is 3)",
+            self.segments,
+        );
+
+        mesh.reserve_for_vertices(2 * self.segments + 2);
+        mesh.reserve_for_faces(4 * self.segments);
+        let mut out = Out { positions: vertex_positions, attributes };
+        out.reserve(2 * self.segments + 2);
+
+        let half = Vec3::new(0.0, 0.0, self.height / 2.0);
+        let (bottom_center, top_center) = (self.center - half, self.center + half);
+        let bottom = add_ring(
+            mesh, &mut out, bottom_center, self.radius, self.segments, 0.0,
+            |dir| dir - Vec3::unit_z(),
+        );
+        let top = add_ring(
+            mesh, &mut out, top_center, self.radius, self.segments, 1.0,
+            |dir| dir + Vec3::unit_z(),
+        );
+
+        let n = bottom.len();
+        for i in 0..n {
+            let j = (i + 1) % n;
+            mesh.add_triangle([bottom[i], bottom[j], top[j]]);
+            mesh.add_triangle([bottom[i], top[j], top[i]]);
+        }
+
+        add_cap(mesh, &mut out, &bottom, bottom_center, false, 0.0);
+        add_cap(mesh, &mut out, &top, top_center, true, 1.0);
+    }
+}
+
+
+/// A closed cone whose axis is parallel to the z-axis, with its flat base at
+/// the bottom and its apex at the top.
+///
+/// The base is a fan of triangles around a center vertex, the side is a fan
+/// of triangles around the apex.
+///
+/// The outline vertices are shared by the side and the base, so their normal
+/// is the average of the side and base normal. The apex normal points along
+/// the axis (the side's normal is undefined there), as does the base
+/// center's. Texture coordinates: `u` grows with the angle (starting in +x
+/// direction), `v` is 0 at the base and 1 at the apex, which gets `u = 0.5`.
+#[derive(Debug, Clone, Copy)]
+pub struct Cone {
+    /// The center of the cone (halfway between the base center and the
+    /// apex). *Default*: `[0, 0, 0]`.
+    pub center: Point3<f64>,
+
+    /// The radius of the base. *Default*: 1.0.
+    pub radius: f64,
+
+    /// The distance between base and apex. *Default*: 2.0.
+    pub height: f64,
+
+    /// The number of vertices on the base's outline. Value must be greater
+    /// than or equal to 3 or else building the cone will panic. *Default*:
+    /// 24.
+    pub segments: hsize,
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Self {
+            center: Point3::origin(),
+            radius: 1.0,
+            height: 2.0,
+            segments: 24,
+        }
+    }
+}
+
+impl Shape for Cone {
+    fn build_with_attributes_to<MeshT, MapT>(
+        &self,
+        mesh: &mut MeshT,
+        vertex_positions: &mut MapT,
+        attributes: &mut VertexAttributes,
+    )
+    where
+        MeshT: MeshMut,
+        MapT: PropStoreMut<VertexHandle>,
+        MapT::Target: Pos3Like,
+    {
+        assert!(
+            self.segments >= 3,
+            "trying to build a cone with {} segments (minimum is 3)",
+            self.segments,
+        );
+
+        mesh.reserve_for_vertices(self.segments + 2);
+        mesh.reserve_for_faces(2 * self.segments);
+        let mut out = Out { positions: vertex_positions, attributes };
+        out.reserve(self.segments + 2);
+
+        let half = Vec3::new(0.0, 0.0, self.height / 2.0);
+        let base_center = self.center - half;
+        let base = add_ring(
+            mesh, &mut out, base_center, self.radius, self.segments, 0.0,
+            |dir| {
+                let side = (dir * self.height + Vec3::unit_z() * self.radius).normalized();
+                side - Vec3::unit_z()
+            },
+        );
+
+        let apex = out.add_vertex(mesh, self.center + half, Vec3::unit_z(), Point2::new(0.5, 1.0));
+        for i in 0..base.len() {
+            mesh.add_triangle([base[i], base[(i + 1) % base.len()], apex]);
+        }
+
+        add_cap(mesh, &mut out, &base, base_center, false, 0.0);
+    }
+}
+
+
+/// A torus lying in the XY-plane, i.e. the z-axis goes through its hole.
+///
+/// The surface is a grid of `major_segments` × `minor_segments` quads (two
+/// triangles each), wrapped around in both directions. Vertex normals point
+/// away from the tube's center circle. The texture coordinates `u` and `v`
+/// grow from 0 to 1 around the major and minor circle, respectively, with
+/// the faces at the seams wrapping back to 0.
+#[derive(Debug, Clone, Copy)]
+pub struct Torus {
+    /// The center of the torus (in the middle of its hole). *Default*:
+    /// `[0, 0, 0]`.
+    pub center: Point3<f64>,
+
+    /// The distance from the center to the center of the tube. *Default*:
+    /// 1.0.
+    pub major_radius: f64,
+
+    /// The radius of the tube. Should be smaller than `major_radius` to avoid
+    /// self intersections. *Default*: 0.25.
+    pub minor_radius: f64,
+
+    /// The number of segments around the z-axis. Value must be greater than
+    /// or equal to 3 or else building the torus will panic. *Default*: 32.
+    pub major_segments: hsize,
+
+    /// The number of segments around the tube. Value must be greater than or
+    /// equal to 3 or else building the torus will panic. *Default*: 12.
+    pub minor_segments: hsize,
+}
+
+impl Default for Torus {
+    fn default() -> Self {
+        Self {
+            center: Point3::origin(),
+            major_radius: 1.0,
+            minor_radius: 0.25,
+            major_segments: 32,
+            minor_segments: 12,
+        }
+    }
+}
+
+impl Shape for Torus {
+    fn build_with_attributes_to<MeshT, MapT>(
+        &self,
+        mesh: &mut MeshT,
+        vertex_positions: &mut MapT,
+        attributes: &mut VertexAttributes,
+    )
+    where
+        MeshT: MeshMut,
+        MapT: PropStoreMut<VertexHandle>,
+        MapT::Target: Pos3Like,
+    {
+        assert!(
+            self.major_segments >= 3,
+            "trying to build a torus with {} major segments (minimum is 3)",
+            self.major_segments,
+        );
+        assert!(
+            self.minor_segments >= 3,
+            "trying to build a torus with {} minor segments (minimum is 3)",
+            self.minor_segments,
+        );
+
+        let vertex_count = self.major_segments * self.minor_segments;
+        mesh.reserve_for_vertices(vertex_count);
+        mesh.reserve_for_faces(2 * vertex_count);
+        let mut out = Out { positions: vertex_positions, attributes };
+        out.reserve(vertex_count);
+
+        let (num_major, num_minor) = (self.major_segments as usize, self.minor_segments as usize);
+        let rings = (0..num_major).map(|i| {
+            let u = (i as f64) / (num_major as f64);
+            let phi = 2.0 * consts::PI * u;
+            let radial = Vec3::new(phi.cos(), phi.sin(), 0.0);
+            let ring_center = self.center + radial * self.major_radius;
+
+            (0..num_minor).map(|j| {
+                let v = (j as f64) / (num_minor as f64);
+                let theta = 2.0 * consts::PI * v;
+                let normal = radial * theta.cos() + Vec3::unit_z() * theta.sin();
+                let pos = ring_center + normal * self.minor_radius;
+                out.add_vertex(mesh, pos, normal, Point2::new(u, v))
+            }).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+
+        for i in 0..num_major {
+            let (ring, next_ring) = (&rings[i], &rings[(i + 1) % num_major]);
+            for j in 0..num_minor {
+                let k = (j + 1) % num_minor;
+                mesh.add_triangle([ring[j], next_ring[j], next_ring[k]]);
+                mesh.add_triangle([ring[j], next_ring[k], ring[k]]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lina::{cross, dot};
+    use crate::{
+        algo::is_closed,
+        core::half_edge::{HalfEdgeMesh, TriConfig},
+    };
+    use super::*;
+
+    type TestMesh = HalfEdgeMesh<TriConfig>;
+
+    /// Returns the volume enclosed by the mesh, which is positive if all faces
+    /// point outwards.
+    fn signed_volume(mesh: &TestMesh, positions: &DenseMap<VertexHandle, Point3<f64>>) -> f64 {
+        mesh.face_handles().map(|f| {
+            let [a, b, c] = mesh.vertices_around_triangle(f).map(|v| positions[v].to_vec());
+            dot(a, cross(b, c)) / 6.0
+        }).sum()
+    }
+
+    #[test]
+    fn sphere() {
+        let sphere = Sphere {
+            center: Point3::new(1.0, 2.0, 3.0),
+            radius: 2.0,
+            num_latitudes: 5,
+            num_longitudes: 8,
+        };
+        let (mesh, positions) = sphere.build::<TestMesh>();
+        mesh.check_integrity();
+
+        assert_eq!(mesh.num_vertices(), 2 + 5 * 8);
+        assert_eq!(mesh.num_faces(), 2 * 8 + 2 * 4 * 8);
+        assert!(is_closed(&mesh));
+        for p in positions.values() {
+            assert!((p.distance_from(sphere.center) - 2.0).abs() < 1e-9);
+        }
+
+        // All faces point outwards, so the volume is positive and a bit
+        // smaller than the one of the real sphere.
+        let volume = signed_volume(&mesh, &positions);
+        let real_volume = 4.0 / 3.0 * consts::PI * 8.0;
+        assert!(volume > 0.7 * real_volume && volume < real_volume, "volume {}", volume);
+    }
+
+    #[test]
+    fn sphere_f32_positions() {
+        let mut mesh = TestMesh::empty();
+        let mut positions = DenseMap::<_, Point3<f32>>::new();
+        Sphere::default().build_to(&mut mesh, &mut positions);
+        assert_eq!(positions.num_props(), mesh.num_vertices());
+        assert!(positions.values().all(|p| (p.to_vec().length() - 1.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn cylinder() {
+        let cylinder = Cylinder {
+            center: Point3::new(0.0, 0.0, 5.0),
+            radius: 2.0,
+            height: 3.0,
+            segments: 64,
+        };
+        let (mesh, positions) = cylinder.build::<TestMesh>();
+        mesh.check_integrity();
+
+        assert_eq!(mesh.num_vertices(), 2 * 64 + 2);
+        assert_eq!(mesh.num_faces(), 4 * 64);
+        assert!(is_closed(&mesh));
+        assert!(positions.values().all(|p| p.z == 3.5 || p.z == 6.5));
+
+        let volume = signed_volume(&mesh, &positions);
+        let real_volume = consts::PI * 4.0 * 3.0;
+        assert!(volume > 0.99 * real_volume && volume < real_volume, "volume {}", volume);
+    }
+
+    #[test]
+    fn cone() {
+        let cone = Cone { segments: 64, ..Cone::default() };
+        let (mesh, positions) = cone.build::<TestMesh>();
+        mesh.check_integrity();
+
+        assert_eq!(mesh.num_vertices(), 64 + 2);
+        assert_eq!(mesh.num_faces(), 2 * 64);
+        assert!(is_closed(&mesh));
+
+        let volume = signed_volume(&mesh, &positions);
+        let real_volume = consts::PI * 2.0 / 3.0;
+        assert!(volume > 0.99 * real_volume && volume < real_volume, "volume {}", volume);
+    }
+
+    /// Checks that all normals have unit length and point away from the
+    /// shape's center and that all texture coordinates are in `[0, 1]`.
+    fn check_attributes(
+        mesh: &TestMesh,
+        positions: &DenseMap<VertexHandle, Point3<f64>>,
+        attrs: &VertexAttributes,
+        center: Point3<f64>,
+    ) {
+        let normals = attrs.normals.as_ref().unwrap();
+        let texcoords = attrs.texcoords.as_ref().unwrap();
+        assert_eq!(normals.num_props(), mesh.num_vertices());
+        assert_eq!(texcoords.num_props(), mesh.num_vertices());
+
+        for v in mesh.vertex_handles() {
+            let n = normals[v];
+            assert!((n.length() - 1.0).abs() < 1e-9);
+            assert!(dot(n, positions[v] - center) > 0.0);
+
+            let t = texcoords[v];
+            assert!((0.0..=1.0).contains(&t.x) && (0.0..=1.0).contains(&t.y));
+        }
+    }
+
+    #[test]
+    fn sphere_attributes() {
+        let sphere = Sphere { center: Point3::new(1.0, 2.0, 3.0), ..Sphere::default() };
+        let (mesh, positions, attrs) = sphere.with_normals().with_texcoords().build::<TestMesh>();
+        check_attributes(&mesh, &positions, &attrs, sphere.center);
+
+        // Sphere normals are exact.
+        let normals = attrs.normals.as_ref().unwrap();
+        let texcoords = attrs.texcoords.as_ref().unwrap();
+        for v in mesh.vertex_handles() {
+            let expected = positions[v] - sphere.center;
+            assert!((normals[v] - expected).length() < 1e-9);
+
+            // `v` is 1 at the north pole and 0 at the south pole
+            let z = positions[v].z - sphere.center.z;
+            assert!((texcoords[v].y - (1.0 - z.acos() / consts::PI)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn cylinder_and_cone_attributes() {
+        let cylinder = Cylinder::default();
+        let (mesh, positions, attrs) = cylinder.with_texcoords().with_normals().build::<TestMesh>();
+        check_attributes(&mesh, &positions, &attrs, cylinder.center);
+
+        let cone = Cone::default();
+        let (mesh, positions, attrs) = cone.with_normals().with_texcoords().build::<TestMesh>();
+        check_attributes(&mesh, &positions, &attrs, cone.center);
+    }
+
+    #[test]
+    fn attributes_only_when_enabled() {
+        let (_, _, attrs) = Sphere::default().with_normals().build::<TestMesh>();
+        assert!(attrs.normals.is_some());
+        assert!(attrs.texcoords.is_none());
+
+        let mut mesh = TestMesh::empty();
+        let mut positions = DenseMap::<_, Point3<f32>>::new();
+        let attrs = Cone::default().with_texcoords().build_to(&mut mesh, &mut positions);
+        assert!(attrs.normals.is_none());
+        assert_eq!(attrs.texcoords.unwrap().num_props(), mesh.num_vertices());
+    }
+
+    #[test]
+    fn torus() {
+        let torus = Torus {
+            center: Point3::new(0.0, 0.0, 1.0),
+            major_radius: 2.0,
+            minor_radius: 0.5,
+            major_segments: 48,
+            minor_segments: 16,
+        };
+        let (mesh, positions, attrs) = torus.with_normals().with_texcoords().build::<TestMesh>();
+        mesh.check_integrity();
+
+        assert_eq!(mesh.num_vertices(), 48 * 16);
+        assert_eq!(mesh.num_faces(), 2 * 48 * 16);
+        assert!(is_closed(&mesh));
+
+        // Genus 1: V - E + F = 0
+        let euler = mesh.num_vertices() as i64 - mesh.num_edges() as i64 + mesh.num_faces() as i64;
+        assert_eq!(euler, 0);
+
+        let volume = signed_volume(&mesh, &positions);
+        let real_volume = 2.0 * consts::PI * consts::PI * 2.0 * 0.5 * 0.5;
+        assert!(volume > 0.95 * real_volume && volume < real_volume, "volume {}", volume);
+
+        let normals = attrs.normals.as_ref().unwrap();
+        for v in mesh.vertex_handles() {
+            let p = positions[v];
+            let ring_center = Vec3::new(p.x, p.y, 0.0).normalized() * 2.0;
+            let expected = (p - torus.center - ring_center) / 0.5;
+            assert!((normals[v] - expected).length() < 1e-9);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "minimum is 3")]
+    fn torus_too_few_segments() {
+        Torus { minor_segments: 2, ..Torus::default() }.build::<TestMesh>();
+    }
+
+    #[test]
+    #[should_panic(expected = "minimum is 3")]
+    fn cone_too_few_segments() {
+        Cone { segments: 2, ..Cone::default() }.build::<TestMesh>();
+    }
+
+    #[test]
+    #[should_panic(expected = "minimum is 3")]
+    fn sphere_too_few_longitudes() {
+        let sphere = Sphere { num_longitudes: 2, ..Sphere::default() };
+        sphere.build::<TestMesh>();
+    }
+}
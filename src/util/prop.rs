@@ -51,6 +51,23 @@ pub trait Pos3Like: Copy {
         )
     }
 
+    /// Casts all three scalar values to the scalar type of `P` with at
+    /// least the fidelity `F` (see [`cast::cast`]) and creates a new value of
+    /// type `P`.
+    ///
+    /// The cast fidelity is checked at compile time, e.g.
+    /// `p.cast_scalar::<cast::Lossless, [f32; 3]>()` fails to compile if `p`
+    /// has `f64` components.
+    fn cast_scalar<F, P>(&self) -> P
+    where
+        F: cast::Fidelity,
+        P: Pos3Like,
+        Self::Scalar: cast::CastInto<P::Scalar>,
+        <Self::Scalar as cast::CastInto<P::Scalar>>::Fidelity: cast::SufficientFor<F>,
+    {
+        self.map_scalar(cast::cast::<F, _, _>)
+    }
+
     fn to_point3(&self) -> Point3<Self::Scalar> {
         self.convert()
     }
@@ -132,6 +149,23 @@ pub trait Vec3Like: Copy {
         )
     }
 
+    /// Casts all three scalar values to the scalar type of `V` with at
+    /// least the fidelity `F` (see [`cast::cast`]) and creates a new value of
+    /// type `V`.
+    ///
+    /// The cast fidelity is checked at compile time, e.g.
+    /// `v.cast_scalar::<cast::Lossless, Vec3<f32>>()` fails to compile if `v`
+    /// has `f64` components.
+    fn cast_scalar<F, V>(&self) -> V
+    where
+        F: cast::Fidelity,
+        V: Vec3Like,
+        Self::Scalar: cast::CastInto<V::Scalar>,
+        <Self::Scalar as cast::CastInto<V::Scalar>>::Fidelity: cast::SufficientFor<F>,
+    {
+        self.map_scalar(cast::cast::<F, _, _>)
+    }
+
     fn to_vec3(&self) -> Vec3<Self::Scalar> {
         self.convert()
     }
@@ -422,4 +456,15 @@ mod tests {
         assert_eq!(f64::color_cast_from(0.0f64), 0.0);
         assert_eq!(f64::color_cast_from(1.0f64), 1.0);
     }
+
+    #[test]
+    fn cast_scalar() {
+        let p = Point3::new(1.5f64, -2.0, 0.25);
+        assert_eq!(p.cast_scalar::<cast::Lossy, [f32; 3]>(), [1.5, -2.0, 0.25]);
+        let p = Point3::new(1u8, 2, 3);
+        assert_eq!(p.cast_scalar::<cast::Lossless, Point3<f64>>(), Point3::new(1.0, 2.0, 3.0));
+
+        let v = Vec3::new(300u16, 3, 0);
+        assert_eq!(v.cast_scalar::<cast::Clamping, [u8; 3]>(), [255, 3, 0]);
+    }
 }
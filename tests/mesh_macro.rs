@@ -174,3 +174,44 @@ fn poly_faces() {
     assert_eq!(mesh.vertices_around_face(FaceHandle::new(0)).count(), 3);
     assert_eq!(mesh.vertices_around_face(FaceHandle::new(1)).count(), 4);
 }
+
+#[test]
+fn edge_props() {
+    use lox::{
+        EdgeHandle, VertexHandle,
+        core::{HalfEdgeMesh, half_edge::TriConfig},
+        map::DenseMap,
+    };
+
+    let mesh = mesh! {
+        type: HalfEdgeMesh<TriConfig>,
+        vertices: [v0, v1, v2, v3],
+        faces: [
+            [v0, v1, v2],
+            [v0, v3, v1],
+            [v0, v2, v3],
+            [v1, v3, v2],
+        ],
+    };
+    assert_eq!(mesh.num_edges(), 6);
+
+    // Crease weight per edge: the edges around `v0` are sharp.
+    let v0 = VertexHandle::new(0);
+    let creases = mesh.edges()
+        .map(|e| {
+            let sharp = e.endpoints().iter().any(|v| v.handle() == v0);
+            (e.handle(), if sharp { 1.0 } else { 0.0 })
+        })
+        .collect::<DenseMap<EdgeHandle, f32>>();
+
+    assert_eq!(creases.num_props(), 6);
+    for e in mesh.edge_handles() {
+        assert!(creases.contains_handle(e));
+    }
+
+    let edge = |a, b| mesh.edge_between_vertices(VertexHandle::new(a), VertexHandle::new(b)).unwrap();
+    assert_eq!(creases[edge(0, 1)], 1.0);
+    assert_eq!(creases[edge(3, 0)], 1.0);
+    assert_eq!(creases[edge(1, 2)], 0.0);
+    assert_eq!(creases[edge(2, 3)], 0.0);
+}